@@ -3,11 +3,12 @@ out vec4 FragColor;
   
 in vec4 Color;
 in vec2 TexCoords;
+in vec4 Chnl;
 
 uniform sampler2D screenTexture;
 
 void main()
 { 
-    float k = texture(screenTexture, TexCoords).r;
+    float k = dot(texture(screenTexture, TexCoords), Chnl);
     FragColor = vec4(Color.r, Color.g, Color.b, Color.a * k);
 }
\ No newline at end of file
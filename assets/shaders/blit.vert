@@ -3,13 +3,16 @@
 layout (location = 0) in vec2 aPos;
 layout (location = 1) in vec2 aTexCoords;
 layout (location = 2) in vec4 aColor;
+layout (location = 3) in vec4 aChnl;
 
 out vec4 Color;
 out vec2 TexCoords;
+out vec4 Chnl;
 
 void main()
 {
     gl_Position = vec4(aPos.x, aPos.y, 0.0, 1.0); 
     TexCoords = aTexCoords;
     Color = aColor;
+    Chnl = aChnl;
 } 
\ No newline at end of file
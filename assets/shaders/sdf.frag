@@ -0,0 +1,23 @@
+#version 330 core
+out vec4 FragColor;
+  
+in vec4 Color;
+in vec2 TexCoords;
+in vec4 Chnl;
+
+uniform sampler2D screenTexture;
+uniform bool msdf;
+
+float median(float r, float g, float b)
+{
+    return max(min(r, g), min(max(r, g), b));
+}
+
+void main()
+{ 
+    vec4 texel = texture(screenTexture, TexCoords);
+    float d = msdf ? median(texel.r, texel.g, texel.b) : dot(texel, Chnl);
+    float w = fwidth(d);
+    float k = smoothstep(0.5 - w, 0.5 + w, d);
+    FragColor = vec4(Color.r, Color.g, Color.b, Color.a * k);
+}
\ No newline at end of file
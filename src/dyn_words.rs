@@ -1,12 +1,15 @@
+use crate::easing::Easing;
+use crate::effects::{Effect, GlyphInfo, Pose};
 use crate::glx::{types::*, FontMonkey};
+use crate::phrases::Phrases;
 
 use rand::prelude::*;
 
 use std::mem;
+use std::str::FromStr;
 
-fn to_vec(list: &str) -> Vec<String> {
-    list.rsplit('\n').filter(|u| !u.is_empty()).map(|u| u.to_owned()).collect()
-}
+const GEN_ATTEMPTS: usize = 16;
+const PLACE_ATTEMPTS: usize = 32;
 
 fn rng_color<R: Rng>(rng: &mut R) -> Rgb {
     let h: f32 = rng.gen_range(0.0..360.0);
@@ -27,83 +30,196 @@ fn rng_color<R: Rng>(rng: &mut R) -> Rgb {
     }
 }
 
+fn gen_span<R: Rng>(rng: &mut R, lo: f32, hi: f32) -> f32 {
+    if lo < hi {
+        rng.gen_range(lo..hi)
+    } else {
+        lo
+    }
+}
+
+fn fit_span(v: f32, lo: f32, hi: f32) -> f32 {
+    if lo < hi {
+        v.clamp(lo, hi)
+    } else {
+        lo
+    }
+}
+
+/// Spawn placement with respect to live words. Words are always placed fully on screen if they
+/// fit. Overlaps are judged on measured word bounds, prior to any effect displacement.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Placement {
+    /// Uniformly random, overlaps allowed.
+    Random,
+    /// Avoid live words, falling back to the least overlapping candidate position.
+    Avoid,
+    /// Avoid live words, skipping the spawn if no free position is found.
+    Strict,
+}
+
+impl FromStr for Placement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "avoid" => Ok(Self::Avoid),
+            "strict" => Ok(Self::Strict),
+            u => Err(format!("invalid placement: {}", u)),
+        }
+    }
+}
+
 pub struct DynWords {
     words_u: Vec<DynWord>,
     words_v: Vec<DynWord>,
-    head: Vec<String>,
-    tail: Vec<String>,
-    frame_hi: u32,
-    frame_lo: u32,
+    phrases: Phrases,
+    effects: Vec<Box<dyn Effect>>,
+    easing: Easing,
+    placement: Placement,
+    life_hi: f32,
+    life_lo: f32,
     cap: usize,
-    width: u32,
-    height: u32,
-    p: f64,
+    rate: f64,
 }
 
 impl DynWords {
+    /// Word lifetimes in seconds, `rate` in expected words spawned per second.
+    /// Each word is animated by an effect picked at random from `effects`, which must not be empty.
     pub fn new(
         word_cap: usize,
-        width: u32,
-        height: u32,
-        frame_hi: u32,
-        frame_lo: u32,
-        p: f64,
+        phrases: Phrases,
+        effects: Vec<Box<dyn Effect>>,
+        life_hi: f32,
+        life_lo: f32,
+        rate: f64,
     ) -> Self {
-        let head = to_vec(include_str!("../assets/words/moods.txt"));
-        let tail = to_vec(include_str!("../assets/words/animals.txt"));
+        assert!(!effects.is_empty(), "no effects");
         Self {
             words_u: Vec::with_capacity(word_cap),
             words_v: Vec::with_capacity(word_cap),
-            head,
-            tail,
-            frame_hi,
-            frame_lo,
+            phrases,
+            effects,
+            easing: Easing::default(),
+            placement: Placement::Avoid,
+            life_hi,
+            life_lo,
             cap: word_cap,
-            width,
-            height,
-            p,
+            rate,
         }
     }
 
-    pub fn push<R: Rng>(&mut self, monkey: &mut FontMonkey, rng: &mut R) {
+    /// Advance the animation by `dt` seconds, retiring expired words and spawning new ones.
+    pub fn update<R: Rng>(&mut self, dt: f32, monkey: &FontMonkey, rng: &mut R) {
         debug_assert!(self.words_v.is_empty());
         for mut word in self.words_u.drain(..) {
-            if word.push(monkey) {
+            if word.update(dt) {
                 self.words_v.push(word);
             }
         }
         mem::swap(&mut self.words_u, &mut self.words_v);
-        self.gen_p(rng);
+        self.gen_p(self.rate * dt as f64, monkey, rng);
+    }
+
+    /// Entry/ exit easing applied to word effects. Linear by default.
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing;
+    }
+
+    /// Defaults to `Placement::Avoid`.
+    pub fn set_placement(&mut self, placement: Placement) {
+        self.placement = placement;
     }
 
-    fn gen_p<R: Rng>(&mut self, rng: &mut R) {
-        let mut p = self.p;
+    pub fn push(&self, monkey: &mut FontMonkey) {
+        for word in &self.words_u {
+            word.push(monkey, self.effects[word.effect].as_ref(), &self.easing);
+        }
+    }
+
+    /// Following a screen size change, pull live words back on screen.
+    pub fn fit(&mut self, monkey: &FontMonkey) {
+        for word in &mut self.words_u {
+            word.fit(monkey);
+        }
+    }
+
+    fn gen_p<R: Rng>(&mut self, mut p: f64, monkey: &FontMonkey, rng: &mut R) {
         while self.words_u.len() != self.cap {
             if p >= 1.0 {
-                self.gen(rng);
+                self.gen(monkey, rng);
                 p -= 1.0;
             } else {
                 if rng.gen_bool(p) {
-                    self.gen(rng);
+                    self.gen(monkey, rng);
                 }
                 break;
             }
         }
     }
 
-    fn gen<R: Rng>(&mut self, rng: &mut R) {
-        let head = &self.head[rng.gen_range(0..self.head.len())];
-        let tail = &self.tail[rng.gen_range(0..self.tail.len())];
-        let word = format!("{} {}", head, tail);
-        let frames = rng.gen_range(self.frame_lo..=self.frame_hi);
-        self.words_u.push(DynWord::new(&word, rng, self.width, self.height, frames));
+    /// Phrases already on screen are not repeated. If we fail to generate a fresh phrase within a
+    /// few attempts, we skip this spawn. Unused phrases are returned to the shuffle bags.
+    fn gen<R: Rng>(&mut self, monkey: &FontMonkey, rng: &mut R) {
+        let mut word = None;
+        for _ in 0..GEN_ATTEMPTS {
+            let u = self.phrases.gen(rng);
+            if !self.words_u.iter().any(|w| w.is(&u)) {
+                word = Some(u);
+                break;
+            }
+            self.phrases.reject();
+        }
+        let word = match word {
+            Some(word) => word,
+            None => return,
+        };
+        let bounds = monkey.measure(&word);
+        let pos = match self.place(bounds, rng) {
+            Some(pos) => pos,
+            None => {
+                self.phrases.reject();
+                return;
+            }
+        };
+        let life = rng.gen_range(self.life_lo..=self.life_hi);
+        let effect = rng.gen_range(0..self.effects.len());
+        self.words_u.push(DynWord::new(&word, rng, pos, bounds, life, effect));
+    }
+
+    /// Rejection sampling, which at our word counts is cheap enough.
+    fn place<R: Rng>(&self, bounds: Rect, rng: &mut R) -> Option<P2> {
+        let mut best: Option<(f32, P2)> = None;
+        for _ in 0..PLACE_ATTEMPTS {
+            let x = gen_span(rng, -1.0 - bounds.min.x, 1.0 - bounds.max.x);
+            let y = gen_span(rng, -1.0 - bounds.min.y, 1.0 - bounds.max.y);
+            let pos = P2::new(x, y);
+            if self.placement == Placement::Random {
+                return Some(pos);
+            }
+            let rect = bounds.offset(pos);
+            let overlap: f32 = self.words_u.iter().map(|u| u.rect().overlap(&rect)).sum();
+            if overlap == 0.0 {
+                return Some(pos);
+            }
+            match best {
+                Some((u, _)) if u <= overlap => {}
+                _ => best = Some((overlap, pos)),
+            }
+        }
+        match self.placement {
+            Placement::Strict => None,
+            _ => best.map(|(_, pos)| pos),
+        }
     }
 }
 
 pub struct DynWord {
     chars: Vec<DynChar>,
     color: Rgb,
-    frames: u32,
+    effect: usize,
+    bounds: Rect,
     x: f32,
     y: f32,
     t: f32,
@@ -111,42 +227,205 @@ pub struct DynWord {
 }
 
 impl DynWord {
-    pub fn new<R: Rng>(str: &str, rng: &mut R, width: u32, height: u32, frames: u32) -> Self {
+    /// `bounds` as measured by `FontMonkey::measure`, relative to `pos`.
+    /// `life` in seconds, `effect` indexes the owner's effects.
+    pub fn new<R: Rng>(
+        str: &str,
+        rng: &mut R,
+        pos: P2,
+        bounds: Rect,
+        life: f32,
+        effect: usize,
+    ) -> Self {
         let color = rng_color(rng);
-        let x = (rng.gen_range(0..width) as f32 + 0.5) / width as f32;
-        let y = (rng.gen_range(0..height) as f32 + 0.5) / height as f32;
-        let x = x * 2.0 - 1.0;
-        let y = y * 2.0 - 1.0;
+        let P2 { x, y } = pos;
         let chars = str
             .chars()
             .map(|c| {
-                let k = rng.gen_range(-2.0..2.0);
+                let k = rng.gen_range(-1.0..1.0);
                 DynChar { k, c }
             })
             .collect();
         let t = -1.0;
-        let ts = 2.0 / frames as f32;
-        Self { x, y, color, chars, frames, t, ts }
+        let ts = 2.0 / life;
+        Self { x, y, color, effect, bounds, chars, t, ts }
     }
 
-    pub fn push(&mut self, monkey: &mut FontMonkey) -> bool {
-        if self.frames == 0 {
-            return false;
-        }
-        let alpha = 1.0 - self.t.abs();
-        let color = self.color.into_rgba(alpha);
+    pub fn fit(&mut self, monkey: &FontMonkey) {
+        let str: String = self.chars.iter().map(|u| u.c).collect();
+        let bounds = monkey.measure(&str);
+        self.x = fit_span(self.x, -1.0 - bounds.min.x, 1.0 - bounds.max.x);
+        self.y = fit_span(self.y, -1.0 - bounds.min.y, 1.0 - bounds.max.y);
+        self.bounds = bounds;
+    }
+
+    /// Screen bounds, prior to any effect displacement.
+    pub fn rect(&self) -> Rect {
+        self.bounds.offset(P2::new(self.x, self.y))
+    }
+
+    pub fn is(&self, str: &str) -> bool {
+        self.chars.iter().map(|u| u.c).eq(str.chars())
+    }
+
+    /// Advance by `dt` seconds, returns false once expired.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.t += self.ts * dt;
+        self.t < 1.0
+    }
+
+    /// Glyph poses are applied about each glyph centre, then word poses about the word centre.
+    /// Pose components are evaluated with `t` eased as per `easing`.
+    pub fn push(&self, monkey: &mut FontMonkey, effect: &dyn Effect, easing: &Easing) {
+        let ts =
+            [easing.position.apply(self.t), easing.alpha.apply(self.t), easing.scale.apply(self.t)];
+        let bounds = self.bounds;
+        let px = monkey.pixel_size();
+        let line_height = monkey.line_height();
+        let centre =
+            P2::new(self.x + (bounds.min.x + bounds.max.x) * 0.5, self.y - line_height * 0.5);
+        let word = compose(ts.map(|t| effect.word(t)));
+        let pos = P2::new(centre.x + word.offset.x, centre.y + word.offset.y);
+        let word_transform = word.transform();
+        let len = self.chars.len();
         let mut x = self.x;
-        let y = self.t * self.t * self.t.signum();
-        for c in &mut self.chars {
-            x += monkey.push_char(c.c, P2::new(x, self.y + y * c.k), color);
+        let mut prev = None;
+        for (index, c) in self.chars.iter().enumerate() {
+            if let Some(prev) = prev {
+                x += monkey.kerning(prev, c.c);
+            }
+            let info = GlyphInfo { index, len, k: c.k };
+            let glyph = compose(ts.map(|t| effect.glyph(t, info)));
+            let color = mul(mul(self.color.into_rgba(1.0), word.color), glyph.color);
+            // Glyph centre relative to the word centre, in pixels.
+            let advance = monkey.advance(c.c);
+            let d = P2::new((x + advance * 0.5 - centre.x) / px.x, 0.0);
+            let o = P2::new(glyph.offset.x / px.x, glyph.offset.y / px.y);
+            let transform = Affine2::translate(-d.x, -d.y)
+                .then(glyph.transform())
+                .then(Affine2::translate(d.x + o.x, d.y + o.y))
+                .then(word_transform);
+            let pen = P2::new(x - centre.x, self.y - centre.y);
+            monkey.push_char_affine(c.c, pos, pen, &transform, color);
+            x += advance;
+            prev = Some(c.c);
         }
-        self.t += self.ts;
-        self.frames -= 1;
-        true
     }
 }
 
+/// Position, alpha and scale poses into one.
+fn compose([position, alpha, scale]: [Pose; 3]) -> Pose {
+    Pose { color: alpha.color, scale: scale.scale, ..position }
+}
+
+fn mul(u: Rgba, v: Rgba) -> Rgba {
+    Rgba::new(u.r * v.r, u.g * v.g, u.b * v.b, (u.a * v.a).clamp(0.0, 1.0))
+}
+
 pub struct DynChar {
     k: f32,
     c: char,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::effects::Builtin;
+
+    use std::path::Path;
+
+    fn monkey() -> FontMonkey {
+        let font =
+            bmfont_rs::text::from_str(include_str!("../assets/fonts/anton_latin.fnt")).unwrap();
+        let image_data = include_bytes!("../assets/fonts/anton_latin_0.png");
+        FontMonkey::load_static(font, &[image_data], 640, 480).unwrap()
+    }
+
+    fn dyn_words(placement: Placement) -> DynWords {
+        let phrases =
+            Phrases::parse("[m]\nCat\n[templates]\n{m}\n", "test", Path::new("")).unwrap();
+        let mut words = DynWords::new(64, phrases, vec![Box::new(Builtin::Fade)], 1.0, 1.0, 1.0);
+        words.set_placement(placement);
+        words
+    }
+
+    fn live(words: &mut DynWords, rect: Rect, rng: &mut StdRng) {
+        words.words_u.push(DynWord::new("Cat", rng, P2::new(0.0, 0.0), rect, 1.0, 0));
+    }
+
+    fn overlap(words: &DynWords, rect: Rect) -> f32 {
+        words.words_u.iter().map(|u| u.rect().overlap(&rect)).sum()
+    }
+
+    fn assert_on_screen(rect: Rect) {
+        assert!(rect.min.x >= -1.0 && rect.max.x <= 1.0, "{:?}", rect);
+        assert!(rect.min.y >= -1.0 && rect.max.y <= 1.0, "{:?}", rect);
+    }
+
+    #[test]
+    fn strict() {
+        let monkey = monkey();
+        let mut words = dyn_words(Placement::Strict);
+        let mut rng = StdRng::seed_from_u64(7);
+        let bounds = monkey.measure("Moody Animals");
+        let mut placed = 0;
+        while let Some(pos) = words.place(bounds, &mut rng) {
+            let rect = bounds.offset(pos);
+            assert_on_screen(rect);
+            assert_eq!(overlap(&words, rect), 0.0);
+            words.words_u.push(DynWord::new("Moody Animals", &mut rng, pos, bounds, 1.0, 0));
+            placed += 1;
+            assert!(placed < 64, "screen never filled");
+        }
+        assert!(placed > 0);
+    }
+
+    #[test]
+    fn strict_full() {
+        let monkey = monkey();
+        let mut words = dyn_words(Placement::Strict);
+        let mut rng = StdRng::seed_from_u64(7);
+        live(&mut words, Rect::new(P2::new(-1.0, -1.0), P2::new(1.0, 1.0)), &mut rng);
+        assert_eq!(words.place(monkey.measure("Cat"), &mut rng), None);
+    }
+
+    #[test]
+    fn avoid_least_overlap() {
+        let mut words = dyn_words(Placement::Avoid);
+        let mut rng = StdRng::seed_from_u64(7);
+        // Every candidate overlaps the live word, by 0.5 - x.
+        live(&mut words, Rect::new(P2::new(-1.0, -1.0), P2::new(0.5, 1.0)), &mut rng);
+        let bounds = Rect::new(P2::new(0.0, -0.1), P2::new(1.0, 0.1));
+        let mut candidates = rng.clone();
+        let least = (0..PLACE_ATTEMPTS)
+            .map(|_| {
+                let x = gen_span(&mut candidates, -1.0 - bounds.min.x, 1.0 - bounds.max.x);
+                let y = gen_span(&mut candidates, -1.0 - bounds.min.y, 1.0 - bounds.max.y);
+                overlap(&words, bounds.offset(P2::new(x, y)))
+            })
+            .fold(f32::INFINITY, f32::min);
+        assert!(least > 0.0);
+        let pos = words.place(bounds, &mut rng).unwrap();
+        assert_on_screen(bounds.offset(pos));
+        assert_eq!(overlap(&words, bounds.offset(pos)), least);
+        words.set_placement(Placement::Strict);
+        assert_eq!(words.place(bounds, &mut rng), None);
+    }
+
+    #[test]
+    fn on_screen() {
+        let monkey = monkey();
+        let mut rng = StdRng::seed_from_u64(7);
+        for &placement in [Placement::Random, Placement::Avoid, Placement::Strict].iter() {
+            let mut words = dyn_words(placement);
+            for str in ["Cat", "Moody Animals", "The quick brown fox"].iter().cycle().take(48) {
+                let bounds = monkey.measure(str);
+                if let Some(pos) = words.place(bounds, &mut rng) {
+                    assert_on_screen(bounds.offset(pos));
+                    words.words_u.push(DynWord::new(str, &mut rng, pos, bounds, 1.0, 0));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! Easing curves for word entry and exit.
+//!
+//! Curves map progress in [0, 1] to eased progress, from 0 to 1. Some curves (`elastic`, `back`)
+//! overshoot in between. Curve names follow the usual convention, e.g. `cubic-in`, `cubic-out`,
+//! `cubic-in-out`, plus `linear` and CSS style `bezier(x1,y1,x2,y2)`.
+
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Curve {
+    Quad,
+    Cubic,
+    Sine,
+    Expo,
+    Elastic,
+    Back,
+    Bounce,
+}
+
+impl Curve {
+    pub const ALL: [Curve; 7] =
+        [Self::Quad, Self::Cubic, Self::Sine, Self::Expo, Self::Elastic, Self::Back, Self::Bounce];
+
+    pub const NAMES: [&'static str; 7] =
+        ["quad", "cubic", "sine", "expo", "elastic", "back", "bounce"];
+
+    /// Ease in form.
+    pub fn ease_in(self, t: f32) -> f32 {
+        match self {
+            Self::Quad => t * t,
+            Self::Cubic => t * t * t,
+            Self::Sine => 1.0 - (t * PI * 0.5).cos(),
+            Self::Expo if t <= 0.0 => 0.0,
+            Self::Expo => 2.0f32.powf(10.0 * t - 10.0),
+            Self::Elastic if t <= 0.0 || t >= 1.0 => t.clamp(0.0, 1.0),
+            Self::Elastic => {
+                -(2.0f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+            }
+            Self::Back => {
+                let c = 1.70158;
+                (c + 1.0) * t * t * t - c * t * t
+            }
+            Self::Bounce => 1.0 - bounce_out(1.0 - t),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    In,
+    Out,
+    InOut,
+}
+
+/// Cubic bezier from (0, 0) to (1, 1) with control points (x1, y1), (x2, y2), x in [0, 1].
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CubicBezier {
+    x1: f32,
+    y1: f32,
+    x2: f32,
+    y2: f32,
+}
+
+impl CubicBezier {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+            return Err(format!("invalid bezier control points, x outside [0, 1]: {}, {}", x1, x2));
+        }
+        if !y1.is_finite() || !y2.is_finite() {
+            return Err(format!("invalid bezier control points, y not finite: {}, {}", y1, y2));
+        }
+        Ok(Self { x1, y1, x2, y2 })
+    }
+
+    pub fn ease(&self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        // x(s) is monotonic for x1, x2 in [0, 1]: bisection, then a few Newton steps.
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..16 {
+            let mid = (lo + hi) * 0.5;
+            if bezier(self.x1, self.x2, mid) < x {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut s = (lo + hi) * 0.5;
+        for _ in 0..4 {
+            let dx = bezier_slope(self.x1, self.x2, s);
+            if dx.abs() < 1e-6 {
+                break;
+            }
+            s = (s - (bezier(self.x1, self.x2, s) - x) / dx).clamp(0.0, 1.0);
+        }
+        bezier(self.y1, self.y2, s)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Ease {
+    Linear,
+    Curve(Curve, Mode),
+    Bezier(CubicBezier),
+}
+
+impl Ease {
+    pub fn ease(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Self::Linear => t,
+            Self::Curve(curve, Mode::In) => curve.ease_in(t),
+            Self::Curve(curve, Mode::Out) => 1.0 - curve.ease_in(1.0 - t),
+            Self::Curve(curve, Mode::InOut) if t < 0.5 => curve.ease_in(2.0 * t) * 0.5,
+            Self::Curve(curve, Mode::InOut) => 1.0 - curve.ease_in(2.0 - 2.0 * t) * 0.5,
+            Self::Bezier(bezier) => bezier.ease(t),
+        }
+    }
+}
+
+impl FromStr for Ease {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "linear" {
+            return Ok(Self::Linear);
+        }
+        if let Some(u) = s.strip_prefix("bezier(").and_then(|u| u.strip_suffix(')')) {
+            let v = u
+                .split(',')
+                .map(|u| u.trim().parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("invalid bezier: {}", s))?;
+            return match v.as_slice() {
+                &[x1, y1, x2, y2] => CubicBezier::new(x1, y1, x2, y2).map(Self::Bezier),
+                _ => Err(format!("invalid bezier, expected 4 control values: {}", s)),
+            };
+        }
+        let (name, mode) = if let Some(u) = s.strip_suffix("-in-out") {
+            (u, Mode::InOut)
+        } else if let Some(u) = s.strip_suffix("-in") {
+            (u, Mode::In)
+        } else if let Some(u) = s.strip_suffix("-out") {
+            (u, Mode::Out)
+        } else {
+            return Err(format!(
+                "invalid easing, expected '-in', '-out' or '-in-out' suffix: {}",
+                s
+            ));
+        };
+        match Curve::NAMES.iter().position(|&u| u == name) {
+            Some(index) => Ok(Self::Curve(Curve::ALL[index], mode)),
+            None => Err(format!("invalid easing: {}", s)),
+        }
+    }
+}
+
+/// Entry and exit curves for a word's animation parameter `t`, which runs from -1 (spawn) through
+/// 0 (fully shown) to 1 (expiry).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Transition {
+    pub entry: Ease,
+    pub exit: Ease,
+}
+
+impl Transition {
+    pub const fn new(entry: Ease, exit: Ease) -> Self {
+        Self { entry, exit }
+    }
+
+    pub fn apply(&self, t: f32) -> f32 {
+        if t < 0.0 {
+            self.entry.ease(t + 1.0) - 1.0
+        } else {
+            self.exit.ease(t)
+        }
+    }
+}
+
+impl Default for Transition {
+    fn default() -> Self {
+        Self::new(Ease::Linear, Ease::Linear)
+    }
+}
+
+impl FromStr for Transition {
+    type Err = String;
+
+    /// `ENTRY[:EXIT]`, exit defaults to entry.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (entry, exit) = match s.find(':') {
+            Some(index) => (&s[..index], &s[index + 1..]),
+            None => (s, s),
+        };
+        Ok(Self::new(entry.parse()?, exit.parse()?))
+    }
+}
+
+/// Word transitions by pose component. Linear by default.
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Easing {
+    /// Offset and rotation.
+    pub position: Transition,
+    /// Color, including alpha.
+    pub alpha: Transition,
+    pub scale: Transition,
+}
+
+pub fn bounce_out(t: f32) -> f32 {
+    let (n, d) = (7.5625, 2.75);
+    if t < 1.0 / d {
+        n * t * t
+    } else if t < 2.0 / d {
+        let t = t - 1.5 / d;
+        n * t * t + 0.75
+    } else if t < 2.5 / d {
+        let t = t - 2.25 / d;
+        n * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / d;
+        n * t * t + 0.984375
+    }
+}
+
+fn bezier(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+fn bezier_slope(p1: f32, p2: f32, s: f32) -> f32 {
+    let r = 1.0 - s;
+    3.0 * r * r * p1 + 6.0 * r * s * (p2 - p1) + 3.0 * s * s * (1.0 - p2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-3;
+
+    fn assert_near(u: f32, v: f32) {
+        assert!((u - v).abs() < EPSILON, "{} != {}", u, v);
+    }
+
+    fn eases() -> Vec<Ease> {
+        let mut eases = vec![Ease::Linear, "bezier(0.25,0.1,0.25,1)".parse().unwrap()];
+        for &curve in Curve::ALL.iter() {
+            for &mode in [Mode::In, Mode::Out, Mode::InOut].iter() {
+                eases.push(Ease::Curve(curve, mode));
+            }
+        }
+        eases
+    }
+
+    #[test]
+    fn endpoints() {
+        for ease in eases() {
+            assert_near(ease.ease(0.0), 0.0);
+            assert_near(ease.ease(1.0), 1.0);
+            assert_near(ease.ease(-1.0), 0.0);
+            assert_near(ease.ease(2.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn in_out_midpoint() {
+        for &curve in Curve::ALL.iter() {
+            assert_near(Ease::Curve(curve, Mode::InOut).ease(0.5), 0.5);
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_near(Ease::Curve(Curve::Quad, Mode::In).ease(0.5), 0.25);
+        assert_near(Ease::Curve(Curve::Cubic, Mode::Out).ease(0.5), 0.875);
+        assert_near(Ease::Curve(Curve::Sine, Mode::In).ease(0.5), 1.0 - 0.5f32.sqrt());
+        assert_near(Ease::Curve(Curve::Bounce, Mode::Out).ease(0.5), 0.765625);
+    }
+
+    #[test]
+    fn bezier_css() {
+        // CSS ease, ease-in, ease-out and ease-in-out.
+        let ease = |s: &str, t: f32| s.parse::<Ease>().unwrap().ease(t);
+        assert_near(ease("bezier(0.25,0.1,0.25,1)", 0.5), 0.8024);
+        assert_near(ease("bezier(0.42,0,1,1)", 0.5), 0.3153);
+        assert_near(ease("bezier(0,0,0.58,1)", 0.5), 0.6847);
+        assert_near(ease("bezier(0.42,0,0.58,1)", 0.5), 0.5);
+        assert_near(ease("bezier(0.5,0.5,0.5,0.5)", 0.3), 0.3);
+    }
+
+    #[test]
+    fn bezier_invalid() {
+        assert!(CubicBezier::new(1.5, 0.0, 0.5, 1.0).unwrap_err().contains("x outside [0, 1]"));
+        assert!(CubicBezier::new(0.5, f32::NAN, 0.5, 1.0).unwrap_err().contains("y not finite"));
+        assert!(CubicBezier::new(0.5, -0.5, 0.5, 1.5).is_ok());
+    }
+
+    #[test]
+    fn transition_apply() {
+        let transition = Transition::new(
+            Ease::Curve(Curve::Quad, Mode::In),
+            Ease::Curve(Curve::Cubic, Mode::In),
+        );
+        assert_near(transition.apply(-1.0), -1.0);
+        assert_near(transition.apply(-0.5), -0.75);
+        assert_near(transition.apply(0.0), 0.0);
+        assert_near(transition.apply(0.5), 0.125);
+        assert_near(transition.apply(1.0), 1.0);
+        let transition = Transition::default();
+        for &t in [-1.0, -0.25, 0.0, 0.25, 1.0].iter() {
+            assert_near(transition.apply(t), t);
+        }
+    }
+
+    #[test]
+    fn from_str() {
+        assert_eq!("linear".parse(), Ok(Ease::Linear));
+        assert_eq!("cubic-in".parse(), Ok(Ease::Curve(Curve::Cubic, Mode::In)));
+        assert_eq!("back-out".parse(), Ok(Ease::Curve(Curve::Back, Mode::Out)));
+        assert_eq!("elastic-in-out".parse(), Ok(Ease::Curve(Curve::Elastic, Mode::InOut)));
+        assert_eq!(
+            "bezier(0.25, 0.1, 0.25, 1)".parse(),
+            CubicBezier::new(0.25, 0.1, 0.25, 1.0).map(Ease::Bezier)
+        );
+        assert!("cubic".parse::<Ease>().is_err());
+        assert!("wobble-in".parse::<Ease>().is_err());
+        assert!("bezier(0.25,0.1,0.25)".parse::<Ease>().is_err());
+        assert!("bezier(0.25,0.1,0.25,x)".parse::<Ease>().is_err());
+        assert!("bezier(2,0,0,1)".parse::<Ease>().is_err());
+        let quad_in = Ease::Curve(Curve::Quad, Mode::In);
+        assert_eq!("quad-in".parse(), Ok(Transition::new(quad_in, quad_in)));
+        assert_eq!("quad-in:linear".parse(), Ok(Transition::new(quad_in, Ease::Linear)));
+        assert!("quad-in:".parse::<Transition>().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Word animation effects.
+//!
+//! An effect maps a word's animation parameter `t` to a pose, first for the word as a whole and
+//! then for each glyph. `t` runs from -1 (spawn) through 0 (fully shown) to 1 (expiry).
+
+use crate::easing::bounce_out;
+use crate::glx::types::*;
+
+use std::f32::consts::PI;
+use std::str::FromStr;
+
+/// Offset in screen units, color multiplier and scale/ rotation (radians, counter-clockwise)
+/// about the word or glyph centre.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pose {
+    pub offset: P2,
+    pub color: Rgba,
+    pub scale: f32,
+    pub rotation: f32,
+}
+
+impl Pose {
+    pub const IDENTITY: Pose = Pose {
+        offset: P2::new(0.0, 0.0),
+        color: Rgb::WHITE.into_rgba(1.0),
+        scale: 1.0,
+        rotation: 0.0,
+    };
+
+    pub const fn alpha(alpha: f32) -> Self {
+        Self { color: Rgb::WHITE.into_rgba(alpha), ..Self::IDENTITY }
+    }
+
+    /// Scale then rotation, in pixels.
+    pub fn transform(&self) -> Affine2 {
+        Affine2::scale(self.scale, self.scale).then(Affine2::rotate(self.rotation))
+    }
+}
+
+/// Glyph position within its word, with a per-glyph random value `k` in [-1, 1).
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GlyphInfo {
+    pub index: usize,
+    pub len: usize,
+    pub k: f32,
+}
+
+pub trait Effect {
+    /// Whole word pose, defaults to a linear fade in/ out.
+    fn word(&self, t: f32) -> Pose {
+        Pose::alpha(1.0 - t.abs())
+    }
+
+    /// Glyph pose, relative to the word pose.
+    fn glyph(&self, _t: f32, _glyph: GlyphInfo) -> Pose {
+        Pose::IDENTITY
+    }
+}
+
+/// Built in effects.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Builtin {
+    /// Glyphs drift in and out vertically, each at its own speed.
+    Scatter,
+    /// Glyphs are revealed one at a time, the word fades out.
+    Typewriter,
+    /// Glyphs ride a travelling sine wave.
+    Wave,
+    /// The word drops in with a bounce, then fades out.
+    Bounce,
+    /// Glyphs spiral in and out, spinning.
+    Spiral,
+    /// Fade in/ out only.
+    Fade,
+    /// Glyphs jitter.
+    Shake,
+    /// The word grows in and shrinks out.
+    Zoom,
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 8] = [
+        Self::Scatter,
+        Self::Typewriter,
+        Self::Wave,
+        Self::Bounce,
+        Self::Spiral,
+        Self::Fade,
+        Self::Shake,
+        Self::Zoom,
+    ];
+
+    pub const NAMES: [&'static str; 8] =
+        ["scatter", "typewriter", "wave", "bounce", "spiral", "fade", "shake", "zoom"];
+}
+
+impl FromStr for Builtin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::NAMES.iter().position(|&u| u == s) {
+            Some(index) => Ok(Self::ALL[index]),
+            None => Err(format!("invalid effect: {}", s)),
+        }
+    }
+}
+
+impl Effect for Builtin {
+    fn word(&self, t: f32) -> Pose {
+        match self {
+            Self::Typewriter => Pose::alpha(1.0 - t.max(0.0)),
+            Self::Bounce if t < 0.0 => {
+                let offset = P2::new(0.0, 0.5 * (1.0 - bounce_out(t + 1.0)));
+                Pose { offset, ..Pose::alpha((2.0 * (t + 1.0)).min(1.0)) }
+            }
+            Self::Bounce => Pose::alpha(1.0 - t),
+            Self::Zoom => Pose { scale: 1.0 - t.abs(), ..Pose::alpha(1.0 - t.abs()) },
+            _ => Pose::alpha(1.0 - t.abs()),
+        }
+    }
+
+    fn glyph(&self, t: f32, glyph: GlyphInfo) -> Pose {
+        let GlyphInfo { index, len, k } = glyph;
+        let index = index as f32;
+        match self {
+            Self::Scatter => Pose { offset: P2::new(0.0, 2.0 * k * t * t.abs()), ..Pose::IDENTITY },
+            Self::Typewriter if t < 0.0 => {
+                Pose::alpha(((t + 1.0) * len as f32 - index).clamp(0.0, 1.0))
+            }
+            Self::Wave => {
+                let y = 0.03 * (6.0 * PI * t - 0.7 * index).sin();
+                Pose { offset: P2::new(0.0, y), ..Pose::IDENTITY }
+            }
+            Self::Spiral => {
+                let r = t * t * (0.4 + 0.2 * k);
+                let theta = 3.0 * PI * t + index;
+                let offset = P2::new(r * theta.cos(), r * theta.sin());
+                Pose { offset, rotation: 2.0 * PI * t, ..Pose::IDENTITY }
+            }
+            Self::Shake => {
+                let a = 0.004 * (1.0 + 2.0 * t.abs());
+                let x = (97.0 * t + 13.1 * index + 7.0 * k).sin();
+                let y = (89.0 * t + 7.3 * index + 11.0 * k).cos();
+                Pose { offset: P2::new(a * x, a * y), ..Pose::IDENTITY }
+            }
+            _ => Pose::IDENTITY,
+        }
+    }
+}
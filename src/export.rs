@@ -0,0 +1,96 @@
+//! Offscreen frame export: PNG sequence, animated GIF or YUV4MPEG2 (Y4M) video.
+//!
+//! GIF frame delays are stored in 1/100 s units, so frame rates that don't divide 100, e.g. 60, are
+//! rounded. Y4M frames are written as 8-bit BT.601 4:4:4, which ffmpeg reads directly:
+//!
+//! `ffmpeg -i out.y4m -pix_fmt yuv420p out.mp4`
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::result::Result;
+
+pub enum Export {
+    Png { dir: PathBuf, frame: u32 },
+    Gif { encoder: GifEncoder<BufWriter<File>>, delay: Delay },
+    Y4m { writer: BufWriter<File>, buffer: Vec<u8> },
+}
+
+impl Export {
+    /// PNG sequence, `frame_00000.png` onwards, in `dir`.
+    pub fn png(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        Ok(Self::Png { dir: dir.to_owned(), frame: 0 })
+    }
+
+    /// Animated GIF or Y4M video, by `path` extension.
+    pub fn create(path: &Path, width: u32, height: u32, fps: u32) -> Result<Self, Box<dyn Error>> {
+        match path.extension().and_then(OsStr::to_str).map(str::to_ascii_lowercase).as_deref() {
+            Some("gif") => {
+                let mut encoder = GifEncoder::new(BufWriter::new(File::create(path)?));
+                encoder.set_repeat(Repeat::Infinite)?;
+                let delay = Delay::from_numer_denom_ms(1000, fps);
+                Ok(Self::Gif { encoder, delay })
+            }
+            Some("y4m") => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                writeln!(writer, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", width, height, fps)?;
+                let buffer = Vec::with_capacity(width as usize * height as usize * 3);
+                Ok(Self::Y4m { writer, buffer })
+            }
+            _ => {
+                Err(format!("{}: unsupported export format, expected .gif or .y4m", path.display())
+                    .into())
+            }
+        }
+    }
+
+    pub fn push(&mut self, image: RgbaImage) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Png { dir, frame } => {
+                image.save(dir.join(format!("frame_{:05}.png", frame)))?;
+                *frame += 1;
+            }
+            Self::Gif { encoder, delay } => {
+                encoder.encode_frame(Frame::from_parts(image, 0, 0, *delay))?;
+            }
+            Self::Y4m { writer, buffer } => {
+                yuv444(&image, buffer);
+                writer.write_all(b"FRAME\n")?;
+                writer.write_all(buffer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush any buffered output.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Png { .. } => {}
+            Self::Gif { encoder, .. } => drop(encoder),
+            Self::Y4m { mut writer, .. } => writer.flush()?,
+        }
+        Ok(())
+    }
+}
+
+/// Planar BT.601 limited range Y, Cb, Cr. Alpha is ignored.
+fn yuv444(image: &RgbaImage, buffer: &mut Vec<u8>) {
+    let n = image.width() as usize * image.height() as usize;
+    buffer.clear();
+    buffer.resize(n * 3, 0);
+    let (y, uv) = buffer.split_at_mut(n);
+    let (u, v) = uv.split_at_mut(n);
+    for (i, pixel) in image.pixels().enumerate() {
+        let [r, g, b, _] = pixel.0;
+        let (r, g, b) = (r as f32, g as f32, b as f32);
+        y[i] = (16.0 + 0.257 * r + 0.504 * g + 0.098 * b).round() as u8;
+        u[i] = (128.0 - 0.148 * r - 0.291 * g + 0.439 * b).round() as u8;
+        v[i] = (128.0 + 0.439 * r - 0.368 * g - 0.071 * b).round() as u8;
+    }
+}
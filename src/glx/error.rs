@@ -1,3 +1,4 @@
+#[cfg(feature = "sdl")]
 use sdl2::video;
 
 use std::error;
@@ -37,6 +38,7 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "sdl")]
 impl From<video::WindowBuildError> for Error {
     fn from(err: video::WindowBuildError) -> Self {
         Self::Sdl(format!("WindowBuildError: {}", err))
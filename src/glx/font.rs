@@ -1,178 +1,391 @@
 //! Font helper objects and functions.
 
-use crate::glx;
-use crate::glx::objects::*;
 use crate::glx::types::*;
 
 use bmfont_rs::Char;
+use bmfont_rs::Chnl;
+use bmfont_rs::Common;
 use bmfont_rs::Font;
-use gl::types::*;
-use image::imageops;
-use image::{GrayImage, ImageFormat};
+use bmfont_rs::Kerning;
+use bmfont_rs::Packing;
+use image::{DynamicImage, GenericImageView, ImageFormat};
 
+use std::collections::HashMap;
 use std::error::Error;
-use std::fs::{self, File};
-use std::mem;
+use std::fs;
 use std::path::Path;
-use std::ptr;
 use std::result::Result;
+use std::str::FromStr;
+
+/// BMFont descriptor file formats.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FontFormat {
+    Text,
+    Binary,
+    Xml,
+    Json,
+}
+
+impl FontFormat {
+    /// Detect the format from the leading bytes, defaulting to text.
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"BMF\x03") {
+            return Self::Binary;
+        }
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        match bytes.iter().find(|u| !u.is_ascii_whitespace()) {
+            Some(b'<') => Self::Xml,
+            Some(b'{') => Self::Json,
+            _ => Self::Text,
+        }
+    }
+
+    pub fn parse(self, bytes: &[u8]) -> Result<Font, Box<dyn Error>> {
+        Ok(match self {
+            Self::Text => bmfont_rs::text::from_bytes(bytes)?,
+            Self::Binary => bmfont_rs::binary::from_bytes(bytes)?,
+            Self::Xml => bmfont_rs::xml::from_bytes(bytes)?,
+            Self::Json => serde_json::from_slice(bytes)?,
+        })
+    }
+}
+
+impl FromStr for FontFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "binary" => Ok(Self::Binary),
+            "xml" => Ok(Self::Xml),
+            "json" => Ok(Self::Json),
+            u => Err(format!("invalid font format: {}", u)),
+        }
+    }
+}
+
+/// Page texture content.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field {
+    /// Coverage bitmap, render with the Blit program.
+    Bitmap,
+    /// Single channel signed distance field, render with the Sdf program.
+    Sdf,
+    /// Multi-channel (RGB) signed distance field, render with the Sdf program.
+    Msdf,
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bitmap" => Ok(Self::Bitmap),
+            "sdf" => Ok(Self::Sdf),
+            "msdf" => Ok(Self::Msdf),
+            u => Err(format!("invalid field: {}", u)),
+        }
+    }
+}
 
 pub struct FontMonkey {
-    buffer: Vec<Blit>,
-    chars: [CharLite; 128],
-    cap: usize,
-    vao: VertexArrayObject,
-    vbo: BufferObject,
-    texture: TextureObject,
+    pages: Vec<Page>,
+    chars: HashMap<char, CharLite>,
+    fallback: Option<CharLite>,
+    kernings: HashMap<(char, char), f32>,
+    kerning: bool,
+    field: Field,
+    scale: f32,
+    x_k: f32,
+    y_k: f32,
+    line_height: f32,
+    base: f32,
+    size: f32,
 }
 
 impl FontMonkey {
+    /// `format` of `None` detects the descriptor format from the file contents.
     pub fn load(
         folder: impl AsRef<Path>,
         font: impl AsRef<Path>,
+        format: Option<FontFormat>,
         screen_width: u32,
         screen_height: u32,
-        cap: usize,
     ) -> Result<Self, Box<dyn Error>> {
         let folder: &Path = folder.as_ref();
         let font: &Path = font.as_ref();
 
-        let rdr = File::open(folder.join(font))?;
-        let font = bmfont_rs::text::from_reader(rdr)?;
+        let bytes = fs::read(folder.join(font))?;
+        let format = format.unwrap_or_else(|| FontFormat::detect(&bytes));
+        let font = format.parse(&bytes)?;
 
-        if font.pages.len() != 1 {
-            return Err("single page support only".into());
-        }
-        let page = &font.pages[0];
-
-        let image_data = fs::read(folder.join(page))?;
+        let pages = font
+            .pages
+            .iter()
+            .map(|page| fs::read(folder.join(page)))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Self::load_static(font, &image_data, screen_width, screen_height, cap)
+        Self::load_static(font, &pages, screen_width, screen_height)
     }
 
-    pub fn load_static(
+    /// `pages` holds the PNG image data for each page, in the font's page order.
+    pub fn load_static<D: AsRef<[u8]>>(
         font: Font,
-        image_data: &[u8],
+        pages: &[D],
         screen_width: u32,
         screen_height: u32,
-        cap: usize,
     ) -> Result<Self, Box<dyn Error>> {
-        let mut image = image::load_from_memory_with_format(image_data, ImageFormat::Png)
-            .map(|u| u.into_luma8())?;
-
-        if image.width() != font.common.scale_w as u32
-            || image.height() != font.common.scale_h as u32
-        {
-            return Err("incongruent texture dimensions".into());
+        if pages.is_empty() || pages.len() != font.pages.len() {
+            return Err("incongruent page count".into());
+        }
+        if font.chars.iter().any(|c| c.page as usize >= pages.len()) {
+            return Err("invalid char page".into());
         }
-        imageops::flip_vertical_in_place(&mut image);
 
-        let texture = build_texture(&image)?;
+        let pages = pages
+            .iter()
+            .map(|image_data| Page::load(image_data.as_ref(), &font))
+            .collect::<Result<Vec<_>, _>>()?;
 
         let x_k = 2.0 / screen_width as f32;
         let y_k = 2.0 / screen_height as f32;
         let u_k = 1.0 / font.common.scale_w as f32;
         let v_k = 1.0 / font.common.scale_h as f32;
 
-        let chars = chars_lossy(u_k, v_k, x_k, y_k, &font.chars);
+        let chars = char_lites(u_k, v_k, x_k, y_k, &font.common, &font.chars);
+        let fallback = chars.get(&'?').or_else(|| chars.get(&'\0')).copied();
+        let kernings = kernings(x_k, &font.kernings);
 
-        let Vaos { vao, vbo } = Vaos::build(cap)?;
+        Ok(Self {
+            pages,
+            chars,
+            fallback,
+            kernings,
+            kerning: true,
+            field: Field::Bitmap,
+            scale: 1.0,
+            x_k,
+            y_k,
+            line_height: font.common.line_height as f32 * y_k,
+            base: font.common.base as f32 * y_k,
+            size: match font.info.size.unsigned_abs() {
+                0 => font.common.line_height as f32,
+                u => u as f32,
+            },
+        })
+    }
 
-        let buffer = Vec::with_capacity(cap);
+    /// Recompute glyph screen metrics following a screen size change.
+    pub fn set_screen_size(&mut self, screen_width: u32, screen_height: u32) {
+        let x_k = 2.0 / screen_width as f32;
+        let y_k = 2.0 / screen_height as f32;
+        let (x_r, y_r) = (x_k / self.x_k, y_k / self.y_k);
+        for char_lite in self.chars.values_mut().chain(self.fallback.as_mut()) {
+            *char_lite = char_lite.rescale(x_r, y_r);
+        }
+        for kerning in self.kernings.values_mut() {
+            *kerning *= x_r;
+        }
+        self.line_height *= y_r;
+        self.base *= y_r;
+        self.x_k = x_k;
+        self.y_k = y_k;
+    }
 
-        Ok(Self { buffer, chars, cap, vao, vbo, texture })
+    /// Size of a screen pixel in screen units.
+    pub fn pixel_size(&self) -> P2 {
+        P2::new(self.x_k, self.y_k)
     }
 
-    pub fn clear(&mut self) {
-        self.buffer.clear();
+    /// Set the glyph rendered in place of characters missing from the font, `None` to skip them.
+    /// Defaults to '?', or the font's null (id=0) glyph if '?' is not present.
+    /// If the font does not contain `c`, missing characters are skipped.
+    pub fn set_fallback(&mut self, c: Option<char>) {
+        self.fallback = c.and_then(|u| self.chars.get(&u)).copied();
     }
 
-    pub fn moo(&mut self) {
-        let color = Rgb::BLUE.into_rgba(1.0);
-        let v0 = Blit::new(P2::new(0.0, 0.0), P2::new(0.0, 0.0), color);
-        let v1 = Blit::new(P2::new(0.0, 1.0), P2::new(0.0, 1.0), color);
-        let v2 = Blit::new(P2::new(1.0, 1.0), P2::new(1.0, 1.0), color);
-        let v3 = Blit::new(P2::new(1.0, 0.0), P2::new(1.0, 0.0), color);
-        self.buffer.push(v1);
-        self.buffer.push(v0);
-        self.buffer.push(v2);
-        self.buffer.push(v3);
-        self.buffer.push(v2);
-        self.buffer.push(v0);
-    }
-
-    /// Blit type program must be bound.
-    pub fn draw(&mut self) {
-        self.bind_texture();
-        self.buffer_data();
-        self.draw_arrays();
-    }
-
-    fn bind_texture(&self) {
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.texture.name());
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+    /// Enable/ disable kerning pair adjustments. Enabled by default.
+    pub fn set_kerning(&mut self, kerning: bool) {
+        self.kerning = kerning;
+    }
+
+    /// Screen advance adjustment between two consecutive characters.
+    pub fn kerning(&self, first: char, second: char) -> f32 {
+        if self.kerning {
+            self.kernings.get(&(first, second)).map_or(0.0, |u| u * self.scale)
+        } else {
+            0.0
         }
-        glx::check_debug();
-    }
-
-    fn buffer_data(&mut self) {
-        unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo.name());
-            if self.buffer.len() > self.cap {
-                self.cap = self.buffer.capacity();
-                gl::BufferData(
-                    gl::ARRAY_BUFFER,
-                    (self.cap * mem::size_of::<Blit>()) as GLsizeiptr,
-                    self.buffer.as_ptr() as *const GLvoid,
-                    gl::DYNAMIC_DRAW,
-                );
-                glx::check_debug();
-            } else {
-                gl::BufferSubData(
-                    gl::ARRAY_BUFFER,
-                    0,
-                    mem::size_of_val(self.buffer.as_slice()) as GLsizeiptr,
-                    self.buffer.as_ptr() as *const GLvoid,
-                );
-                glx::check_debug();
+    }
+
+    /// Declare the page textures as distance fields. Defaults to `Field::Bitmap`.
+    pub fn set_field(&mut self, field: Field) {
+        self.field = field;
+    }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// Glyph scale relative to the font's native pixel size. Defaults to 1.0.
+    /// Bitmap fonts blur when scaled, distance field fonts remain crisp.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The font's native size in pixels.
+    pub fn size(&self) -> f32 {
+        self.size
+    }
+
+    /// Scale glyphs to the specified font size in pixels.
+    pub fn set_pixel_size(&mut self, pixels: f32) {
+        self.scale = pixels / self.size;
+    }
+
+    /// Line height in screen units.
+    pub fn line_height(&self) -> f32 {
+        self.line_height * self.scale
+    }
+
+    /// Distance from the top of the line to the baseline in screen units.
+    pub fn base(&self) -> f32 {
+        self.base * self.scale
+    }
+
+    /// Screen advance of a single character, zero if neither it nor the fallback is present.
+    pub fn advance(&self, c: char) -> f32 {
+        self.chars.get(&c).or(self.fallback.as_ref()).map_or(0.0, |u| u.a * self.scale)
+    }
+
+    /// Measure `string` as `push_str` would lay it out, relative to `pos`, without pushing any
+    /// vertices. Horizontally the bounds cover glyph extents and advances, vertically the line.
+    pub fn measure(&self, string: &str) -> Rect {
+        let k = self.scale;
+        let mut min_x = 0.0f32;
+        let mut max_x = 0.0f32;
+        let mut pen = 0.0;
+        let mut prev = None;
+        for c in string.chars() {
+            if let Some(prev) = prev {
+                pen += self.kerning(prev, c);
             }
+            if let Some(char_lite) = self.chars.get(&c).or(self.fallback.as_ref()) {
+                min_x = min_x.min(pen + char_lite.x * k);
+                max_x = max_x.max(pen + (char_lite.x + char_lite.xs) * k);
+                pen += char_lite.a * k;
+            }
+            max_x = max_x.max(pen);
+            prev = Some(c);
         }
+        Rect::new(P2::new(min_x, -self.line_height()), P2::new(max_x, 0.0))
     }
 
-    fn draw_arrays(&mut self) {
-        unsafe {
-            gl::BindVertexArray(self.vao.name());
-            gl::DrawArrays(gl::TRIANGLES, 0, self.buffer.len() as GLsizei);
+    pub fn clear(&mut self) {
+        for page in &mut self.pages {
+            page.buffer.clear();
         }
-        glx::check_debug()
+    }
+
+    pub fn moo(&mut self) {
+        let color = Rgb::BLUE.into_rgba(1.0);
+        let chnl = Rgba::new(1.0, 0.0, 0.0, 0.0);
+        let v0 = Blit::new(P2::new(0.0, 0.0), P2::new(0.0, 0.0), color, chnl);
+        let v1 = Blit::new(P2::new(0.0, 1.0), P2::new(0.0, 1.0), color, chnl);
+        let v2 = Blit::new(P2::new(1.0, 1.0), P2::new(1.0, 1.0), color, chnl);
+        let v3 = Blit::new(P2::new(1.0, 0.0), P2::new(1.0, 0.0), color, chnl);
+        let buffer = &mut self.pages[0].buffer;
+        buffer.push(v1);
+        buffer.push(v0);
+        buffer.push(v2);
+        buffer.push(v3);
+        buffer.push(v2);
+        buffer.push(v0);
+    }
+
+    /// Page images, bottom row first as per OpenGL, paired with the vertices pushed to each page.
+    pub fn pages(&self) -> impl Iterator<Item = (&DynamicImage, &[Blit])> {
+        self.pages.iter().map(|page| (&page.image, page.buffer.as_slice()))
     }
 
     pub fn push_str(&mut self, string: &str, mut pos: P2, color: Rgba) -> f32 {
+        let mut prev = None;
         for c in string.chars() {
+            if let Some(prev) = prev {
+                pos.x += self.kerning(prev, c);
+            }
             pos.x += self.push_char(c, pos, color);
+            prev = Some(c);
         }
         pos.x
     }
 
+    /// Push `string` with `transform`, in pixels y up, applied about `pos`.
+    /// Returns the untransformed advance.
+    pub fn push_str_affine(
+        &mut self,
+        string: &str,
+        pos: P2,
+        transform: &Affine2,
+        color: Rgba,
+    ) -> f32 {
+        let mut pen = P2::default();
+        let mut prev = None;
+        for c in string.chars() {
+            if let Some(prev) = prev {
+                pen.x += self.kerning(prev, c);
+            }
+            pen.x += self.push_char_affine(c, pos, pen, transform, color);
+            prev = Some(c);
+        }
+        pen.x
+    }
+
     pub fn push_char(&mut self, c: char, pos: P2, color: Rgba) -> f32 {
-        if (c as usize) < 128 {
-            let CharLite { u, v, us, vs, x, y, xs, ys, a } = self.chars[c as usize];
-            let x = pos.x + x;
-            let y = pos.y + y;
-            let v0 = Blit::new(P2::new(x, y), P2::new(u, v), color);
-            let v1 = Blit::new(P2::new(x, y + ys), P2::new(u, v + vs), color);
-            let v2 = Blit::new(P2::new(x + xs, y + ys), P2::new(u + us, v + vs), color);
-            let v3 = Blit::new(P2::new(x + xs, y), P2::new(u + us, v), color);
-            self.buffer.push(v1);
-            self.buffer.push(v0);
-            self.buffer.push(v2);
-            self.buffer.push(v3);
-            self.buffer.push(v2);
-            self.buffer.push(v0);
+        self.push_char_with(c, color, |u| P2::new(pos.x + u.x, pos.y + u.y))
+    }
+
+    /// Push `c` at `pen`, relative to `pos`, with `transform`, in pixels y up, applied about `pos`.
+    /// Returns the untransformed advance.
+    pub fn push_char_affine(
+        &mut self,
+        c: char,
+        pos: P2,
+        pen: P2,
+        transform: &Affine2,
+        color: Rgba,
+    ) -> f32 {
+        let transform = transform.rescale(self.x_k, self.y_k);
+        self.push_char_with(c, color, |u| {
+            let u = transform.apply(P2::new(pen.x + u.x, pen.y + u.y));
+            P2::new(pos.x + u.x, pos.y + u.y)
+        })
+    }
+
+    fn push_char_with<F>(&mut self, c: char, color: Rgba, map: F) -> f32
+    where
+        F: Fn(P2) -> P2,
+    {
+        if let Some(&CharLite { u, v, us, vs, x, y, xs, ys, a, page, chnl }) =
+            self.chars.get(&c).or(self.fallback.as_ref())
+        {
+            let k = self.scale;
+            let (x, y, xs, ys, a) = (x * k, y * k, xs * k, ys * k, a * k);
+            let v0 = Blit::new(map(P2::new(x, y)), P2::new(u, v), color, chnl);
+            let v1 = Blit::new(map(P2::new(x, y + ys)), P2::new(u, v + vs), color, chnl);
+            let v2 = Blit::new(map(P2::new(x + xs, y + ys)), P2::new(u + us, v + vs), color, chnl);
+            let v3 = Blit::new(map(P2::new(x + xs, y)), P2::new(u + us, v), color, chnl);
+            let buffer = &mut self.pages[page].buffer;
+            buffer.push(v1);
+            buffer.push(v0);
+            buffer.push(v2);
+            buffer.push(v3);
+            buffer.push(v2);
+            buffer.push(v0);
             a
         } else {
             0.0
@@ -180,57 +393,48 @@ impl FontMonkey {
     }
 }
 
-struct Vaos {
-    vao: VertexArrayObject,
-    vbo: BufferObject,
+struct Page {
+    image: DynamicImage,
+    buffer: Vec<Blit>,
 }
 
-impl Vaos {
-    fn build(cap: usize) -> glx::Result<Self> {
-        // VAO gen
-        let vao = VertexArrayObject::gen();
-        glx::check()?;
-        unsafe {
-            gl::BindVertexArray(vao.name());
-        }
-        glx::check()?;
-        // VBO
-        let vbo = BufferObject::gen();
-        unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo.name());
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (cap * mem::size_of::<Blit>()) as GLsizeiptr,
-                ptr::null(),
-                gl::DYNAMIC_DRAW,
-            );
-        }
-        glx::check()?;
-        unsafe { Blit::init_vao() };
-        // Done
-        unsafe {
-            gl::BindVertexArray(0);
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+impl Page {
+    fn load(image_data: &[u8], font: &Font) -> Result<Self, Box<dyn Error>> {
+        let image = image::load_from_memory_with_format(image_data, ImageFormat::Png)?;
+
+        if image.width() != font.common.scale_w as u32
+            || image.height() != font.common.scale_h as u32
+        {
+            return Err("incongruent texture dimensions".into());
         }
-        Ok(Self { vao, vbo })
+        let image = match image {
+            DynamicImage::ImageLuma8(_)
+            | DynamicImage::ImageLumaA8(_)
+            | DynamicImage::ImageRgba8(_) => image.flipv(),
+            u => DynamicImage::ImageRgba8(u.into_rgba8()).flipv(),
+        };
+
+        Ok(Self { image, buffer: Vec::default() })
     }
 }
 
 #[derive(Copy, Clone, Debug, Default)]
 pub struct CharLite {
-    u: f32,  // texture coord u
-    v: f32,  // texture coord v
-    us: f32, // texture coord u size/ width
-    vs: f32, // texture coord v size/ height
-    x: f32,  // screen coord x
-    y: f32,  // screen coord y
-    xs: f32, // screen coord x size/ width
-    ys: f32, // screen coord y size/ height
-    a: f32,  // screen advance
+    u: f32,      // texture coord u
+    v: f32,      // texture coord v
+    us: f32,     // texture coord u size/ width
+    vs: f32,     // texture coord v size/ height
+    x: f32,      // screen coord x
+    y: f32,      // screen coord y
+    xs: f32,     // screen coord x size/ width
+    ys: f32,     // screen coord y size/ height
+    a: f32,      // screen advance
+    page: usize, // texture page index
+    chnl: Rgba,  // texture channel mask
 }
 
 impl CharLite {
+    #[allow(clippy::too_many_arguments)]
     pub const fn new(
         u: f32,
         v: f32,
@@ -241,17 +445,30 @@ impl CharLite {
         xs: f32,
         ys: f32,
         a: f32,
+        page: usize,
+        chnl: Rgba,
     ) -> Self {
-        Self { u, v, us, vs, x, y, xs, ys, a }
+        Self { u, v, us, vs, x, y, xs, ys, a, page, chnl }
+    }
+
+    fn rescale(self, x_r: f32, y_r: f32) -> Self {
+        let Self { x, y, xs, ys, a, .. } = self;
+        Self { x: x * x_r, y: y * y_r, xs: xs * x_r, ys: ys * y_r, a: a * x_r, ..self }
     }
 }
 
-fn chars_lossy(u_k: f32, v_k: f32, x_k: f32, y_k: f32, chars: &[Char]) -> [CharLite; 128] {
-    let mut char_lites = [CharLite::default(); 128];
+fn char_lites(
+    u_k: f32,
+    v_k: f32,
+    x_k: f32,
+    y_k: f32,
+    common: &Common,
+    chars: &[Char],
+) -> HashMap<char, CharLite> {
+    let mut char_lites = HashMap::with_capacity(chars.len());
     for c in chars.iter() {
-        let index = c.id as usize;
-        if index < 128 {
-            char_lites[index] = CharLite::new(
+        if let Some(index) = char::from_u32(c.id) {
+            let char_lite = CharLite::new(
                 c.x as f32 * u_k,
                 1.0 - c.y as f32 * v_k,
                 c.width as f32 * u_k,
@@ -261,30 +478,43 @@ fn chars_lossy(u_k: f32, v_k: f32, x_k: f32, y_k: f32, chars: &[Char]) -> [CharL
                 c.width as f32 * x_k,
                 -(c.height as f32) * y_k,
                 c.xadvance as f32 * x_k,
+                c.page as usize,
+                chnl_mask(common, c.chnl),
             );
+            char_lites.insert(index, char_lite);
         }
     }
     char_lites
 }
 
-fn build_texture(src: &GrayImage) -> glx::Result<TextureObject> {
-    let txo = TextureObject::gen();
-    glx::check()?;
-    unsafe {
-        gl::BindTexture(gl::TEXTURE_2D, txo.name());
-        gl::TexImage2D(
-            gl::TEXTURE_2D,
-            0,
-            gl::R8 as GLint,
-            src.width() as GLsizei,
-            src.height() as GLsizei,
-            0,
-            gl::RED,
-            gl::UNSIGNED_BYTE,
-            src.as_ptr() as *const GLvoid,
-        );
-        gl::BindTexture(gl::TEXTURE_2D, 0);
-    }
-    glx::check()?;
-    Ok(txo)
+/// Select the texture channel that holds the glyph image, preferring glyph over outline data.
+/// Channels set to zero/ one are never selected.
+fn chnl_mask(common: &Common, chnl: Chnl) -> Rgba {
+    let rank = |enabled: bool, packing: Packing| match packing {
+        _ if !enabled => None,
+        Packing::Glyph => Some(0),
+        Packing::GlyphOutline => Some(1),
+        Packing::Outline => Some(2),
+        Packing::Zero | Packing::One => None,
+    };
+    let ranks = [
+        rank(chnl.red(), common.red_chnl),
+        rank(chnl.green(), common.green_chnl),
+        rank(chnl.blue(), common.blue_chnl),
+        rank(chnl.alpha(), common.alpha_chnl),
+    ];
+    let index = (0..4).filter(|&i| ranks[i].is_some()).min_by_key(|&i| ranks[i]).unwrap_or(0);
+    let mut mask = [0.0; 4];
+    mask[index] = 1.0;
+    Rgba::new(mask[0], mask[1], mask[2], mask[3])
+}
+
+fn kernings(x_k: f32, kernings: &[Kerning]) -> HashMap<(char, char), f32> {
+    kernings
+        .iter()
+        .filter_map(|k| match (char::from_u32(k.first), char::from_u32(k.second)) {
+            (Some(first), Some(second)) => Some(((first, second), k.amount as f32 * x_k)),
+            _ => None,
+        })
+        .collect()
 }
@@ -0,0 +1,159 @@
+//! Multi-line text layout.
+
+use crate::glx::types::*;
+use crate::glx::FontMonkey;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+    /// Stretch word gaps to fill the line, paragraph final lines are left aligned.
+    Justify,
+}
+
+/// Character pen position, relative to the line origin.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Glyph {
+    pub c: char,
+    pub x: f32,
+}
+
+/// A glyph run, `y` relative to the layout origin.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Line {
+    pub y: f32,
+    pub width: f32,
+    pub glyphs: Vec<Glyph>,
+}
+
+/// Laid out text, in screen units with the layout origin at the top left of the first line.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Layout {
+    pub lines: Vec<Line>,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Layout {
+    /// Lay out `text` breaking lines on '\n' and, if `max_width` is specified, greedily wrapping
+    /// words to fit. Words wider than `max_width` are not broken. Whitespace runs collapse to a
+    /// single space. Without `max_width`, lines are aligned against the widest line.
+    pub fn build(monkey: &FontMonkey, text: &str, max_width: Option<f32>, align: Align) -> Self {
+        let space = monkey.advance(' ');
+        let mut rows: Vec<(Vec<&str>, bool)> = Vec::default();
+        for paragraph in text.split('\n') {
+            let mut row: Vec<&str> = Vec::default();
+            let mut row_width = 0.0;
+            for word in paragraph.split_whitespace() {
+                let width = word_width(monkey, word);
+                if let (Some(max_width), Some(&last)) = (max_width, row.last()) {
+                    let width = row_width + gap(monkey, last, word, space) + width;
+                    if width > max_width {
+                        rows.push((row, false));
+                        row = Vec::default();
+                        row_width = 0.0;
+                    }
+                }
+                if let Some(&last) = row.last() {
+                    row_width += gap(monkey, last, word, space);
+                }
+                row_width += width;
+                row.push(word);
+            }
+            rows.push((row, true));
+        }
+
+        let widths: Vec<f32> = rows.iter().map(|(row, _)| row_width(monkey, row, space)).collect();
+        let width = max_width.unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+        let line_height = monkey.line_height();
+        let lines = rows
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, ((row, last), row_width))| {
+                let slack = (width - row_width).max(0.0);
+                let (offset, stretch) = match align {
+                    Align::Left => (0.0, 0.0),
+                    Align::Center => (slack / 2.0, 0.0),
+                    Align::Right => (slack, 0.0),
+                    Align::Justify if *last || row.len() < 2 => (0.0, 0.0),
+                    Align::Justify => (0.0, slack / (row.len() - 1) as f32),
+                };
+                let mut line = layout_row(monkey, row, space, offset, stretch);
+                line.y = -(i as f32) * line_height;
+                line
+            })
+            .collect::<Vec<_>>();
+        let height = lines.len() as f32 * line_height;
+        Self { lines, width, height }
+    }
+
+    pub fn push(&self, monkey: &mut FontMonkey, pos: P2, color: Rgba) {
+        for line in &self.lines {
+            for glyph in &line.glyphs {
+                monkey.push_char(glyph.c, P2::new(pos.x + glyph.x, pos.y + line.y), color);
+            }
+        }
+    }
+}
+
+fn layout_row(monkey: &FontMonkey, row: &[&str], space: f32, offset: f32, stretch: f32) -> Line {
+    let mut glyphs = Vec::default();
+    let mut pen = offset;
+    let mut prev: Option<&str> = None;
+    for &word in row {
+        if let Some(prev) = prev {
+            pen += gap(monkey, prev, word, space) + stretch;
+        }
+        let mut prev_c = None;
+        for c in word.chars() {
+            if let Some(prev_c) = prev_c {
+                pen += monkey.kerning(prev_c, c);
+            }
+            glyphs.push(Glyph { c, x: pen });
+            pen += monkey.advance(c);
+            prev_c = Some(c);
+        }
+        prev = Some(word);
+    }
+    Line { y: 0.0, width: pen - offset, glyphs }
+}
+
+fn row_width(monkey: &FontMonkey, row: &[&str], space: f32) -> f32 {
+    let mut width = 0.0;
+    let mut prev: Option<&str> = None;
+    for &word in row {
+        if let Some(prev) = prev {
+            width += gap(monkey, prev, word, space);
+        }
+        width += word_width(monkey, word);
+        prev = Some(word);
+    }
+    width
+}
+
+fn word_width(monkey: &FontMonkey, word: &str) -> f32 {
+    let mut width = 0.0;
+    let mut prev = None;
+    for c in word.chars() {
+        if let Some(prev) = prev {
+            width += monkey.kerning(prev, c);
+        }
+        width += monkey.advance(c);
+        prev = Some(c);
+    }
+    width
+}
+
+/// Space advance between two words, including kerning.
+fn gap(monkey: &FontMonkey, first: &str, second: &str, space: f32) -> f32 {
+    let mut gap = space;
+    if let Some(c) = first.chars().last() {
+        gap += monkey.kerning(c, ' ');
+    }
+    if let Some(c) = second.chars().next() {
+        gap += monkey.kerning(' ', c);
+    }
+    gap
+}
@@ -4,13 +4,21 @@ pub mod types;
 
 mod error;
 mod font;
+mod layout;
 mod objects;
 mod program;
+mod raster;
+mod renderer;
+mod target;
 
 pub use error::{Error, Result};
-pub use font::FontMonkey;
+pub use font::{Field, FontFormat, FontMonkey};
+pub use layout::{Align, Glyph, Layout, Line};
 pub use objects::*;
 pub use program::*;
+pub use raster::CpuRenderer;
+pub use renderer::{GlRenderer, Renderer};
+pub use target::{read_pixels, RenderTarget};
 
 pub fn check_debug() {
     #[cfg(debug_assertions)]
@@ -6,7 +6,7 @@ use gl::types::*;
 use std::ffi::CString;
 use std::ptr;
 
-use super::objects::{ProgramBuilder, ProgramObject};
+use super::objects::{ProgramBuilder, ProgramObject, UniformLocation};
 
 macro_rules! program {
     ($name:ident, $shader:expr $(,$element:ident)*) => {
@@ -42,6 +42,7 @@ macro_rules! program {
 }
 
 program!(Blit, "blit");
+program!(Sdf, "sdf", msdf);
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 #[repr(u32)]
@@ -0,0 +1,177 @@
+//! Pure Rust CPU rasteriser, mirroring the OpenGL pipeline: linear texture filtering with repeat
+//! wrap, the Blit/ Sdf fragment shaders and `SRC_ALPHA, ONE_MINUS_SRC_ALPHA` blending (alpha as
+//! coverage, `ONE, ONE_MINUS_SRC_ALPHA`) into an RGBA8 target.
+
+use crate::glx::font::{Field, FontMonkey};
+use crate::glx::renderer::Renderer;
+use crate::glx::types::*;
+
+use image::{DynamicImage, GenericImageView, Rgba as Pixel, RgbaImage};
+
+/// CPU renderer, draws to an owned image.
+pub struct CpuRenderer {
+    image: RgbaImage,
+}
+
+impl CpuRenderer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { image: RgbaImage::new(width, height) }
+    }
+
+    /// Top row first, as per `read_pixels`.
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+
+    fn triangle(&mut self, texture: &DynamicImage, field: Field, vertices: &[Blit]) {
+        let (width, height) = (self.image.width() as f32, self.image.height() as f32);
+        // NDC to pixels, y down.
+        let p = [0, 1, 2].map(|i| {
+            let pos = vertices[i].pos;
+            P2::new((pos.x + 1.0) * 0.5 * width, (1.0 - pos.y) * 0.5 * height)
+        });
+        let area = edge(p[0], p[1], p[2]);
+        if area == 0.0 {
+            return;
+        }
+        let bary = |q: P2| {
+            [edge(p[1], p[2], q) / area, edge(p[2], p[0], q) / area, edge(p[0], p[1], q) / area]
+        };
+        // Pixels with centres exactly on an edge belong to one triangle only.
+        let owns = |a: P2, b: P2| {
+            let (dx, dy) = ((b.x - a.x) * area.signum(), (b.y - a.y) * area.signum());
+            dy > 0.0 || (dy == 0.0 && dx < 0.0)
+        };
+        let owns = [owns(p[1], p[2]), owns(p[2], p[0]), owns(p[0], p[1])];
+
+        let x_lo = p.iter().map(|u| u.x).fold(f32::INFINITY, f32::min).floor().max(0.0) as u32;
+        let x_hi = p.iter().map(|u| u.x).fold(f32::NEG_INFINITY, f32::max).ceil().min(width) as u32;
+        let y_lo = p.iter().map(|u| u.y).fold(f32::INFINITY, f32::min).floor().max(0.0) as u32;
+        let y_hi =
+            p.iter().map(|u| u.y).fold(f32::NEG_INFINITY, f32::max).ceil().min(height) as u32;
+        for y in y_lo..y_hi {
+            for x in x_lo..x_hi {
+                let q = P2::new(x as f32 + 0.5, y as f32 + 0.5);
+                let w = bary(q);
+                if (0..3).any(|i| w[i] < 0.0 || (w[i] == 0.0 && !owns[i])) {
+                    continue;
+                }
+                let tex_coord = |w: [f32; 3]| {
+                    let (u, v) = (0..3).fold((0.0, 0.0), |(u, v), i| {
+                        (u + w[i] * vertices[i].tex_coord.x, v + w[i] * vertices[i].tex_coord.y)
+                    });
+                    P2::new(u, v)
+                };
+                let color = lerp(w, vertices.iter().map(|u| u.color));
+                let chnl = lerp(w, vertices.iter().map(|u| u.chnl));
+                let k = match field {
+                    Field::Bitmap => dot(sample(texture, tex_coord(w)), chnl),
+                    Field::Sdf | Field::Msdf => {
+                        let d = |w| distance(sample(texture, tex_coord(w)), chnl, field);
+                        // fwidth, by forward differences.
+                        let d0 = d(w);
+                        let dx = d(bary(P2::new(q.x + 1.0, q.y)));
+                        let dy = d(bary(P2::new(q.x, q.y + 1.0)));
+                        let fw = (dx - d0).abs() + (dy - d0).abs();
+                        smoothstep(0.5 - fw, 0.5 + fw, d0)
+                    }
+                };
+                blend(self.image.get_pixel_mut(x, y), color, color.a * k);
+            }
+        }
+    }
+}
+
+impl Renderer for CpuRenderer {
+    fn clear(&mut self, color: Rgba) {
+        let pixel = Pixel([unorm(color.r), unorm(color.g), unorm(color.b), unorm(color.a)]);
+        for u in self.image.pixels_mut() {
+            *u = pixel;
+        }
+    }
+
+    fn draw(&mut self, monkey: &FontMonkey) {
+        let field = monkey.field();
+        for (texture, vertices) in monkey.pages() {
+            for triangle in vertices.chunks_exact(3) {
+                self.triangle(texture, field, triangle);
+            }
+        }
+    }
+}
+
+fn edge(a: P2, b: P2, c: P2) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn lerp<I: Iterator<Item = Rgba>>(w: [f32; 3], values: I) -> Rgba {
+    values.zip(w.iter()).fold(Rgba::default(), |acc, (u, &k)| {
+        Rgba::new(acc.r + u.r * k, acc.g + u.g * k, acc.b + u.b * k, acc.a + u.a * k)
+    })
+}
+
+fn dot(u: Rgba, v: Rgba) -> f32 {
+    u.r * v.r + u.g * v.g + u.b * v.b + u.a * v.a
+}
+
+fn distance(texel: Rgba, chnl: Rgba, field: Field) -> f32 {
+    if field == Field::Msdf {
+        let (r, g, b) = (texel.r, texel.g, texel.b);
+        r.min(g).max(r.max(g).min(b))
+    } else {
+        dot(texel, chnl)
+    }
+}
+
+fn smoothstep(lo: f32, hi: f32, x: f32) -> f32 {
+    if lo >= hi {
+        return if x < lo { 0.0 } else { 1.0 };
+    }
+    let t = ((x - lo) / (hi - lo)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Bilinear filtered sample, repeat wrap. Luma/ luma alpha textures swizzle as per the GL path.
+fn sample(texture: &DynamicImage, tex_coord: P2) -> Rgba {
+    let (width, height) = (texture.width() as i64, texture.height() as i64);
+    let x = tex_coord.x * width as f32 - 0.5;
+    let y = tex_coord.y * height as f32 - 0.5;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let texel = |x: i64, y: i64| {
+        let (x, y) = (x.rem_euclid(width) as u32, y.rem_euclid(height) as u32);
+        let Pixel([r, g, b, a]) = match texture {
+            DynamicImage::ImageLuma8(u) => {
+                let l = u.get_pixel(x, y).0[0];
+                Pixel([l, l, l, l])
+            }
+            u => u.get_pixel(x, y),
+        };
+        Rgba::new(r as f32, g as f32, b as f32, a as f32)
+    };
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let w = [(1.0 - fx) * (1.0 - fy), fx * (1.0 - fy), (1.0 - fx) * fy, fx * fy];
+    let u = [texel(x0, y0), texel(x0 + 1, y0), texel(x0, y0 + 1), texel(x0 + 1, y0 + 1)];
+    let k = 1.0 / 255.0;
+    u.iter().zip(w.iter()).fold(Rgba::default(), |acc, (u, &w)| {
+        let w = w * k;
+        Rgba::new(acc.r + u.r * w, acc.g + u.g * w, acc.b + u.b * w, acc.a + u.a * w)
+    })
+}
+
+/// `SRC_ALPHA, ONE_MINUS_SRC_ALPHA` for color, `ONE, ONE_MINUS_SRC_ALPHA` for alpha.
+fn blend(dst: &mut Pixel<u8>, color: Rgba, alpha: f32) {
+    let alpha = alpha.clamp(0.0, 1.0);
+    let src = [color.r, color.g, color.b, 1.0];
+    for (d, s) in dst.0.iter_mut().zip(src.iter()) {
+        *d = unorm(s.clamp(0.0, 1.0) * alpha + *d as f32 / 255.0 * (1.0 - alpha));
+    }
+}
+
+fn unorm(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
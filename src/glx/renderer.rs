@@ -0,0 +1,214 @@
+//! Renderers that consume FontMonkey vertex output.
+
+use crate::glx;
+use crate::glx::font::{Field, FontMonkey};
+use crate::glx::objects::*;
+use crate::glx::program;
+use crate::glx::types::*;
+
+use gl::types::*;
+use image::{DynamicImage, GenericImageView};
+
+use std::mem;
+use std::ptr;
+
+pub trait Renderer {
+    /// Clear the whole target to `color`.
+    fn clear(&mut self, color: Rgba);
+
+    /// Draw the vertices pushed to `monkey`, as per its field, blending with
+    /// `SRC_ALPHA, ONE_MINUS_SRC_ALPHA`.
+    fn draw(&mut self, monkey: &FontMonkey);
+}
+
+/// OpenGL renderer, draws to the bound framebuffer.
+pub struct GlRenderer {
+    buffer: Vec<Blit>,
+    textures: Vec<TextureObject>,
+    blit: program::Blit,
+    sdf: program::Sdf,
+    cap: usize,
+    vao: VertexArrayObject,
+    vbo: BufferObject,
+}
+
+impl GlRenderer {
+    /// Upload the page textures of `monkey`, which must be the only monkey drawn.
+    /// `cap` is the initial vertex capacity.
+    pub fn build(monkey: &FontMonkey, cap: usize) -> glx::Result<Self> {
+        let textures =
+            monkey.pages().map(|(image, _)| build_texture(image)).collect::<glx::Result<_>>()?;
+        let blit = program::Blit::build()?;
+        let sdf = program::Sdf::build()?;
+        let Vaos { vao, vbo } = Vaos::build(cap)?;
+        let buffer = Vec::with_capacity(cap);
+        Ok(Self { buffer, textures, blit, sdf, cap, vao, vbo })
+    }
+
+    fn use_program(&self, field: Field) {
+        unsafe {
+            match field {
+                Field::Bitmap => gl::UseProgram(self.blit.name()),
+                Field::Sdf | Field::Msdf => {
+                    gl::UseProgram(self.sdf.name());
+                    gl::Uniform1i(self.sdf.msdf().location(), (field == Field::Msdf) as GLint);
+                }
+            }
+            gl::Enable(gl::BLEND);
+            // Alpha accumulates as coverage, so text over an opaque clear stays opaque.
+            gl::BlendFuncSeparate(
+                gl::SRC_ALPHA,
+                gl::ONE_MINUS_SRC_ALPHA,
+                gl::ONE,
+                gl::ONE_MINUS_SRC_ALPHA,
+            );
+        }
+        glx::check_debug();
+    }
+
+    fn buffer_data(&mut self) {
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo.name());
+            if self.buffer.len() > self.cap {
+                self.cap = self.buffer.capacity();
+                gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.cap * mem::size_of::<Blit>()) as GLsizeiptr,
+                    self.buffer.as_ptr() as *const GLvoid,
+                    gl::DYNAMIC_DRAW,
+                );
+                glx::check_debug();
+            } else {
+                gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    mem::size_of_val(self.buffer.as_slice()) as GLsizeiptr,
+                    self.buffer.as_ptr() as *const GLvoid,
+                );
+                glx::check_debug();
+            }
+        }
+    }
+
+    fn draw_arrays(&self, first: usize, count: usize) {
+        unsafe {
+            gl::BindVertexArray(self.vao.name());
+            gl::DrawArrays(gl::TRIANGLES, first as GLint, count as GLsizei);
+        }
+        glx::check_debug()
+    }
+}
+
+impl Renderer for GlRenderer {
+    fn clear(&mut self, color: Rgba) {
+        unsafe {
+            gl::ClearColor(color.r, color.g, color.b, color.a);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+        }
+        glx::check_debug();
+    }
+
+    /// Vertices are batched by page, one draw call per non-empty page.
+    fn draw(&mut self, monkey: &FontMonkey) {
+        debug_assert_eq!(monkey.pages().count(), self.textures.len());
+        self.use_program(monkey.field());
+        self.buffer.clear();
+        for (_, vertices) in monkey.pages() {
+            self.buffer.extend_from_slice(vertices);
+        }
+        self.buffer_data();
+        let mut first = 0;
+        for ((_, vertices), texture) in monkey.pages().zip(&self.textures) {
+            let count = vertices.len();
+            if count != 0 {
+                bind_texture(texture);
+                self.draw_arrays(first, count);
+            }
+            first += count;
+        }
+    }
+}
+
+struct Vaos {
+    vao: VertexArrayObject,
+    vbo: BufferObject,
+}
+
+impl Vaos {
+    fn build(cap: usize) -> glx::Result<Self> {
+        // VAO gen
+        let vao = VertexArrayObject::gen();
+        glx::check()?;
+        unsafe {
+            gl::BindVertexArray(vao.name());
+        }
+        glx::check()?;
+        // VBO
+        let vbo = BufferObject::gen();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo.name());
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                (cap * mem::size_of::<Blit>()) as GLsizeiptr,
+                ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+        }
+        glx::check()?;
+        unsafe { Blit::init_vao() };
+        // Done
+        unsafe {
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+        }
+        Ok(Self { vao, vbo })
+    }
+}
+
+fn bind_texture(texture: &TextureObject) {
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_2D, texture.name());
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+    }
+    glx::check_debug();
+}
+
+fn build_texture(src: &DynamicImage) -> glx::Result<TextureObject> {
+    let (internal_format, format, swizzle, data) = match src {
+        // Single channel: glyph data may be declared in any channel, so every channel reads it.
+        DynamicImage::ImageLuma8(u) => (gl::R8, gl::RED, [gl::RED; 4], u.as_raw()),
+        DynamicImage::ImageLumaA8(u) => {
+            (gl::RG8, gl::RG, [gl::RED, gl::RED, gl::RED, gl::GREEN], u.as_raw())
+        }
+        DynamicImage::ImageRgba8(u) => {
+            (gl::RGBA8, gl::RGBA, [gl::RED, gl::GREEN, gl::BLUE, gl::ALPHA], u.as_raw())
+        }
+        _ => return Err(glx::Error::OpenGL("unsupported texture layout".to_owned())),
+    };
+    let swizzle = swizzle.map(|u| u as GLint);
+    let txo = TextureObject::gen();
+    glx::check()?;
+    unsafe {
+        gl::BindTexture(gl::TEXTURE_2D, txo.name());
+        gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl::TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            internal_format as GLint,
+            src.width() as GLsizei,
+            src.height() as GLsizei,
+            0,
+            format,
+            gl::UNSIGNED_BYTE,
+            data.as_ptr() as *const GLvoid,
+        );
+        gl::TexParameteriv(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_RGBA, swizzle.as_ptr());
+        gl::BindTexture(gl::TEXTURE_2D, 0);
+    }
+    glx::check()?;
+    Ok(txo)
+}
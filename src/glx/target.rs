@@ -0,0 +1,94 @@
+//! Offscreen render targets.
+
+use crate::glx;
+use crate::glx::objects::*;
+
+use gl::types::*;
+use image::{imageops, RgbaImage};
+
+/// RGBA framebuffer object backed by a single renderbuffer.
+pub struct RenderTarget {
+    fbo: FramebufferObject,
+    _rbo: RenderbufferObject,
+    width: u32,
+    height: u32,
+}
+
+impl RenderTarget {
+    pub fn build(width: u32, height: u32) -> glx::Result<Self> {
+        let rbo = RenderbufferObject::gen();
+        unsafe {
+            gl::BindRenderbuffer(gl::RENDERBUFFER, rbo.name());
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::RGBA8,
+                width as GLsizei,
+                height as GLsizei,
+            );
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+        glx::check()?;
+        let fbo = FramebufferObject::gen();
+        let status = unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo.name());
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                rbo.name(),
+            );
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+        glx::check()?;
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(glx::Error::OpenGL(format!("incomplete framebuffer: {}", status)));
+        }
+        Ok(Self { fbo, _rbo: rbo, width, height })
+    }
+
+    /// Bind as the draw/ read framebuffer and set the viewport to match.
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo.name());
+            gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei);
+        }
+        glx::check_debug();
+    }
+
+    /// Target must be bound.
+    pub fn read_pixels(&self) -> RgbaImage {
+        read_pixels(self.width, self.height)
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Read back the bound read framebuffer, flipped top row first.
+pub fn read_pixels(width: u32, height: u32) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height);
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            0,
+            0,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            image.as_mut_ptr() as *mut GLvoid,
+        );
+    }
+    glx::check_debug();
+    imageops::flip_vertical_in_place(&mut image);
+    image
+}
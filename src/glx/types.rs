@@ -26,6 +26,7 @@ build!(V2, u: f32, v: f32);
 build!(V3, u: f32, v: f32, w: f32);
 build!(V3Rgb, v: V3, rgb: Rgb);
 build!(Rect2, top_left: V3, bottom_right: V3);
+build!(Rect, min: P2, max: P2);
 build!(Quad, p0: P2, p1: P2, p2: P2, p4: P2);
 build!(Rgb, r: f32, g: f32, b: f32);
 build!(Rgba, r: f32, g: f32, b: f32, a: f32);
@@ -45,9 +46,92 @@ impl Rgb {
     }
 }
 
-build!(Blit, pos: P2, tex_coord: P2, color: Rgba);
+impl Rect {
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn offset(&self, by: P2) -> Self {
+        Self::new(
+            P2::new(self.min.x + by.x, self.min.y + by.y),
+            P2::new(self.max.x + by.x, self.max.y + by.y),
+        )
+    }
+
+    /// Intersection area, zero if disjoint.
+    pub fn overlap(&self, other: &Rect) -> f32 {
+        let w = self.max.x.min(other.max.x) - self.min.x.max(other.min.x);
+        let h = self.max.y.min(other.max.y) - self.min.y.max(other.min.y);
+        w.max(0.0) * h.max(0.0)
+    }
+}
+
+// 2D affine transform: p' = (a * p.x + c * p.y + x, b * p.x + d * p.y + y).
+build!(Affine2, a: f32, b: f32, c: f32, d: f32, x: f32, y: f32);
+
+impl Affine2 {
+    pub const IDENTITY: Affine2 = Affine2::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn translate(x: f32, y: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+    }
+
+    pub const fn scale(x: f32, y: f32) -> Self {
+        Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+    }
+
+    /// Counter-clockwise rotation in radians, y up.
+    pub fn rotate(theta: f32) -> Self {
+        let (sin, cos) = theta.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Shear angles in radians, x along y and y along x.
+    pub fn skew(x: f32, y: f32) -> Self {
+        Self::new(1.0, y.tan(), x.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// Apply `self` followed by `other`.
+    pub fn then(self, other: Self) -> Self {
+        Self::new(
+            other.a * self.a + other.c * self.b,
+            other.b * self.a + other.d * self.b,
+            other.a * self.c + other.c * self.d,
+            other.b * self.c + other.d * self.d,
+            other.a * self.x + other.c * self.y + other.x,
+            other.b * self.x + other.d * self.y + other.y,
+        )
+    }
+
+    #[inline(always)]
+    pub fn apply(&self, p: P2) -> P2 {
+        P2::new(self.a * p.x + self.c * p.y + self.x, self.b * p.x + self.d * p.y + self.y)
+    }
+
+    /// Re-express a transform defined in unit (pixel) space in a space scaled by `x_k`, `y_k`.
+    /// Rotations are only distortion free in unit space, whereas our screen space is not square.
+    pub fn rescale(self, x_k: f32, y_k: f32) -> Self {
+        Self::new(
+            self.a,
+            self.b * y_k / x_k,
+            self.c * x_k / y_k,
+            self.d,
+            self.x * x_k,
+            self.y * y_k,
+        )
+    }
+}
+
+build!(Blit, pos: P2, tex_coord: P2, color: Rgba, chnl: Rgba);
 
 impl Blit {
+    /// # Safety
+    ///
+    /// A vertex array object and an array buffer must be bound.
     pub unsafe fn init_vao() {
         let stride = mem::size_of::<Self>() as GLsizei;
         let mut pointer = 0;
@@ -57,10 +141,35 @@ impl Blit {
         pointer += 2 * mem::size_of::<GLfloat>();
         gl::VertexAttribPointer(2, 4, gl::FLOAT, gl::FALSE, stride, pointer as *const GLvoid);
         pointer += 4 * mem::size_of::<GLfloat>();
+        gl::VertexAttribPointer(3, 4, gl::FLOAT, gl::FALSE, stride, pointer as *const GLvoid);
+        pointer += 4 * mem::size_of::<GLfloat>();
         assert_eq!(pointer, stride as usize);
         gl::EnableVertexAttribArray(0);
         gl::EnableVertexAttribArray(1);
         gl::EnableVertexAttribArray(2);
+        gl::EnableVertexAttribArray(3);
         glx::check_debug();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect {
+        Rect::new(P2::new(x0, y0), P2::new(x1, y1))
+    }
+
+    #[test]
+    fn rect_overlap() {
+        let u = rect(0.0, 0.0, 2.0, 1.0);
+        assert_eq!(u.overlap(&rect(3.0, 0.0, 4.0, 1.0)), 0.0);
+        assert_eq!(u.overlap(&rect(0.0, -2.0, 2.0, -1.0)), 0.0);
+        assert_eq!(u.overlap(&rect(2.0, 0.0, 3.0, 1.0)), 0.0);
+        assert_eq!(u.overlap(&rect(2.0, 1.0, 3.0, 2.0)), 0.0);
+        assert_eq!(u.overlap(&rect(1.0, 0.5, 3.0, 2.0)), 0.5);
+        assert_eq!(u.overlap(&rect(0.5, 0.25, 1.5, 0.75)), 0.5);
+        assert_eq!(rect(0.5, 0.25, 1.5, 0.75).overlap(&u), 0.5);
+        assert_eq!(u.overlap(&u), 2.0);
+    }
+}
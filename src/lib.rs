@@ -0,0 +1,21 @@
+//! Bitmap font rendering and animated word demo components.
+//!
+//! - `glx`: OpenGL helper objects, shader programs, BMFont loading/ layout and renderers, including
+//!   a pure Rust CPU rasteriser.
+//! - `dyn_words`, `easing`, `effects`, `phrases`, `words`: the animated word display, its effects,
+//!   easing curves and phrase generator.
+//! - `export`: offscreen frame export.
+//! - `window`: SDL2 window and OpenGL context creation, requires the `sdl` feature.
+//!
+//! The `demo` feature, enabled by default, builds the `moody_animals` binary. Library users may
+//! disable default features to avoid the SDL2 and CLI dependencies.
+
+pub mod dyn_words;
+pub mod easing;
+pub mod effects;
+pub mod export;
+pub mod glx;
+pub mod phrases;
+#[cfg(feature = "sdl")]
+pub mod window;
+pub mod words;
@@ -1,21 +1,37 @@
-pub mod dyn_words;
-pub mod glx;
+use moody_animals::dyn_words::{DynWords, Placement};
+use moody_animals::easing::{Easing, Transition};
+use moody_animals::effects::{Builtin, Effect};
+use moody_animals::export::Export;
+use moody_animals::glx::{self, types::Rgba};
+use moody_animals::glx::{CpuRenderer, Field, FontFormat, FontMonkey, GlRenderer, Renderer};
+use moody_animals::phrases::{Phrases, Select};
+use moody_animals::window::State;
+use moody_animals::words;
 
-use dyn_words::DynWords;
-
-use clap::{crate_version, App, Arg, ArgMatches};
+use clap::{crate_version, App, Arg, ArgGroup, ArgMatches};
+use gl::types::*;
+use image::RgbaImage;
 use rand::prelude::*;
-use sdl2::event::Event;
+use rand_chacha::ChaCha8Rng;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
-use sdl2::video::{GLContext, GLProfile, SwapInterval, Window};
-use sdl2::{Sdl, VideoSubsystem};
 
 use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::result::Result;
 use std::str::FromStr;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 const TITLE: &str = "Demo";
 
+const DEFAULT_DPI: f32 = 96.0;
+
+/// Fixed animation time step in seconds.
+const TIME_STEP: f32 = 1.0 / 60.0;
+/// Maximum catch up time in seconds, following a stall.
+const LAG_MAX: f32 = 0.25;
+
 const CHAR_CAP: usize = 0x0400;
 const WORD_CAP: usize = 0x0100;
 
@@ -25,6 +41,25 @@ pub struct Param {
     density: u32,
     speed: u32,
     fullscreen: bool,
+    kerning: bool,
+    font: Option<PathBuf>,
+    font_format: Option<FontFormat>,
+    field: Field,
+    text_size: Option<TextSize>,
+    head: Vec<PathBuf>,
+    tail: Vec<PathBuf>,
+    phrases: Option<PathBuf>,
+    select: Select,
+    effects: Vec<Builtin>,
+    easing: Easing,
+    placement: Placement,
+    seed: Option<u64>,
+    headless: Option<PathBuf>,
+    export: Option<PathBuf>,
+    fps: u32,
+    screenshot_dir: PathBuf,
+    frames: u32,
+    cpu: bool,
 }
 
 impl Param {
@@ -35,114 +70,333 @@ impl Param {
             density: get_u32(&args, "density"),
             speed: get_u32(&args, "speed"),
             fullscreen: args.is_present("fullscreen"),
+            kerning: !args.is_present("no-kerning"),
+            font: args.value_of_os("font").map(PathBuf::from),
+            font_format: args.value_of("font-format").map(|u| {
+                FontFormat::from_str(u)
+                    .unwrap_or_else(|_| panic!("INTERNAL: parse value error: font-format"))
+            }),
+            field: args.value_of("field").map_or(Field::Bitmap, |u| {
+                Field::from_str(u).unwrap_or_else(|_| panic!("INTERNAL: parse value error: field"))
+            }),
+            text_size: args.value_of("text-size").map(|u| {
+                TextSize::from_str(u)
+                    .unwrap_or_else(|_| panic!("INTERNAL: parse value error: text-size"))
+            }),
+            head: args
+                .values_of_os("head")
+                .map_or(Vec::default(), |u| u.map(PathBuf::from).collect()),
+            tail: args
+                .values_of_os("tail")
+                .map_or(Vec::default(), |u| u.map(PathBuf::from).collect()),
+            phrases: args.value_of_os("phrases").map(PathBuf::from),
+            select: args.value_of("select").map_or(Select::Random, |u| {
+                Select::from_str(u)
+                    .unwrap_or_else(|_| panic!("INTERNAL: parse value error: select"))
+            }),
+            effects: args.values_of("effect").map_or(vec![Builtin::Scatter], |u| {
+                u.flat_map(|u| match u {
+                    "all" => Builtin::ALL.to_vec(),
+                    u => vec![Builtin::from_str(u)
+                        .unwrap_or_else(|_| panic!("INTERNAL: parse value error: effect"))],
+                })
+                .collect()
+            }),
+            easing: Easing {
+                position: get_transition(&args, "ease-position"),
+                alpha: get_transition(&args, "ease-alpha"),
+                scale: get_transition(&args, "ease-scale"),
+            },
+            placement: args.value_of("placement").map_or(Placement::Avoid, |u| {
+                Placement::from_str(u)
+                    .unwrap_or_else(|_| panic!("INTERNAL: parse value error: placement"))
+            }),
+            seed: some_u64(&args, "seed"),
+            headless: args.value_of_os("headless").map(PathBuf::from),
+            export: args.value_of_os("export").map(PathBuf::from),
+            fps: get_u32(&args, "fps"),
+            screenshot_dir: args.value_of_os("screenshot-dir").map(PathBuf::from).unwrap(),
+            frames: get_u32(&args, "frames"),
+            cpu: args.is_present("cpu"),
         }
     }
 }
 
-pub struct State {
-    sdl: Sdl,
-    _video_subsystem: VideoSubsystem,
-    window: Window,
-    _context: GLContext,
+/// Font size, as opposed to the font's native pixel size.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextSize {
+    /// Points, 1/72 inch, as per the display DPI.
+    Points(f32),
+    /// Percentage of the screen height.
+    Screen(f32),
 }
 
-impl State {
-    pub fn windowed(title: &str, width: u32, height: u32) -> Result<Self, Box<dyn Error>> {
-        Self::build(|vs| vs.window(title, width, height).opengl().build().unwrap())
-    }
-
-    pub fn fullscreen(title: &str) -> Result<Self, Box<dyn Error>> {
-        Self::build(|vs| vs.window(title, 0, 0).fullscreen_desktop().opengl().build().unwrap())
+impl TextSize {
+    pub fn pixels(self, dpi: f32, screen_height: u32) -> f32 {
+        match self {
+            Self::Points(u) => u * dpi / 72.0,
+            Self::Screen(u) => u * screen_height as f32 / 100.0,
+        }
     }
+}
 
-    fn build<W>(window_builder: W) -> Result<Self, Box<dyn Error>>
-    where
-        W: FnOnce(&VideoSubsystem) -> Window,
-    {
-        let sdl = sdl2::init().unwrap();
-        let video_subsystem = sdl.video().unwrap();
-
-        let gl_attr = video_subsystem.gl_attr();
-        gl_attr.set_context_profile(GLProfile::Core);
-        gl_attr.set_context_version(3, 3);
-
-        let window = window_builder(&video_subsystem);
-
-        let context = window.gl_create_context().unwrap();
-        gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
-
-        debug_assert_eq!(gl_attr.context_profile(), GLProfile::Core);
-        debug_assert_eq!(gl_attr.context_version(), (3, 3));
+impl FromStr for TextSize {
+    type Err = String;
 
-        video_subsystem.gl_set_swap_interval(SwapInterval::VSync)?;
-        Ok(Self { sdl, _video_subsystem: video_subsystem, window, _context: context })
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(u) = s.strip_suffix("pt") {
+            parse_size(u).map(Self::Points)
+        } else if let Some(u) = s.strip_suffix('%') {
+            parse_size(u).map(Self::Screen)
+        } else {
+            Err(format!("invalid text size, expected 'pt' or '%' suffix: {}", s))
+        }
     }
 }
 
 pub struct Demo {
     width: u32,
     height: u32,
+    dpi: f32,
     state: State,
 }
 
 impl Demo {
     pub fn new(state: State) -> Result<Self, Box<dyn Error>> {
-        let mode = state.window.display_mode()?;
-        Ok(Self { width: mode.w as u32, height: mode.h as u32, state })
+        // Drawable pixels: the window may be smaller than the display, or high DPI.
+        let (width, height) = state.window.drawable_size();
+        let dpi = state
+            .window
+            .display_index()
+            .and_then(|u| state.video_subsystem.display_dpi(u))
+            .map_or(DEFAULT_DPI, |(_, _, vdpi)| vdpi);
+        Ok(Self { width, height, dpi, state })
     }
 
-    pub fn execute(&mut self, p: f64, frame_hi: u32, frame_lo: u32) -> Result<(), Box<dyn Error>> {
-        let font = bmfont_rs::text::from_str(include_str!("../assets/fonts/anton_latin.fnt"))?;
-        let image_data = include_bytes!("../assets/fonts/anton_latin_0.png");
-
-        let mut monkey =
-            glx::FontMonkey::load_static(font, image_data, self.width, self.height, CHAR_CAP)?;
-        let mut words = DynWords::new(WORD_CAP, self.width, self.height, frame_hi, frame_lo, p);
-        let blit = glx::Blit::build()?;
-        unsafe {
-            gl::UseProgram(blit.name());
-            gl::Enable(gl::BLEND);
-            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-        }
-        let mut rng = thread_rng();
+    pub fn execute(&mut self, param: &Param) -> Result<(), Box<dyn Error>> {
+        let mut scene = Scene::new(param, self.width, self.height, self.dpi)?;
+        let mut renderer = GlRenderer::build(&scene.monkey, CHAR_CAP)?;
         let mut event_pump = self.state.sdl.event_pump().unwrap();
+        let mut instant = Instant::now();
+        let mut lag = 0.0;
+        let mut screenshot = false;
         'running: loop {
-            unsafe {
-                gl::ClearColor(0.0, 0.0, 0.0, 1.0);
-                gl::Clear(gl::COLOR_BUFFER_BIT);
+            let now = Instant::now();
+            lag += now.duration_since(instant).as_secs_f32().min(LAG_MAX);
+            instant = now;
+            while lag >= TIME_STEP {
+                scene.update(TIME_STEP);
+                lag -= TIME_STEP;
+            }
+            scene.render(&mut renderer);
+            if screenshot {
+                screenshot = false;
+                match self.screenshot(&param.screenshot_dir) {
+                    Ok(path) => println!("screenshot: {}", path.display()),
+                    Err(err) => eprintln!("screenshot: {}", err),
+                }
             }
-            words.push(&mut monkey, &mut rng);
-            monkey.draw();
-            monkey.clear();
             self.state.window.gl_swap_window();
             for event in event_pump.poll_iter() {
                 match event {
                     Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                         break 'running
                     }
+                    Event::KeyDown { keycode: Some(Keycode::F12), repeat: false, .. } => {
+                        screenshot = true;
+                    }
+                    Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                        self.resize(param, &mut scene);
+                    }
                     _ => {}
                 }
             }
         }
         Ok(())
     }
+
+    /// Save the back buffer, prior to swap, as a timestamped PNG in `dir`.
+    fn screenshot(&self, dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let millis = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        let path = dir.join(format!("moody_animals_{}.png", millis));
+        unsafe {
+            gl::ReadBuffer(gl::BACK);
+        }
+        glx::read_pixels(self.width, self.height).save(&path)?;
+        Ok(path)
+    }
+
+    /// Render offscreen at `param` width/ height into a framebuffer object.
+    pub fn execute_offscreen(
+        &mut self,
+        param: &Param,
+        export: Export,
+    ) -> Result<(), Box<dyn Error>> {
+        let target = glx::RenderTarget::build(param.width, param.height)?;
+        let scene = Scene::new(param, param.width, param.height, self.dpi)?;
+        let renderer = GlRenderer::build(&scene.monkey, CHAR_CAP)?;
+        target.bind();
+        render_frames(param, scene, renderer, export, |_| target.read_pixels())
+    }
+
+    fn resize(&mut self, param: &Param, scene: &mut Scene) {
+        let (width, height) = self.state.window.drawable_size();
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        unsafe {
+            gl::Viewport(0, 0, width as GLsizei, height as GLsizei);
+        }
+        scene.monkey.set_screen_size(width, height);
+        if let Some(text_size) = param.text_size {
+            scene.monkey.set_pixel_size(text_size.pixels(self.dpi, self.height));
+        }
+        scene.words.fit(&scene.monkey);
+    }
+}
+
+/// Render offscreen at `param` width/ height with the CPU rasteriser, no display or OpenGL
+/// required.
+fn execute_cpu(param: &Param, export: Export) -> Result<(), Box<dyn Error>> {
+    let scene = Scene::new(param, param.width, param.height, DEFAULT_DPI)?;
+    let renderer = CpuRenderer::new(param.width, param.height);
+    render_frames(param, scene, renderer, export, |u| u.image().clone())
+}
+
+/// Render `param.frames` frames, at a fixed time step of `1 / param.fps` seconds.
+fn render_frames<R, F>(
+    param: &Param,
+    mut scene: Scene,
+    mut renderer: R,
+    mut export: Export,
+    read: F,
+) -> Result<(), Box<dyn Error>>
+where
+    R: Renderer,
+    F: Fn(&R) -> RgbaImage,
+{
+    let dt = 1.0 / param.fps as f32;
+    for _ in 0..param.frames {
+        scene.update(dt);
+        scene.render(&mut renderer);
+        export.push(read(&renderer))?;
+    }
+    export.finish()
+}
+
+/// Demo state, independent of the renderer.
+pub struct Scene {
+    monkey: FontMonkey,
+    words: DynWords,
+    rng: ChaCha8Rng,
+}
+
+impl Scene {
+    fn new(param: &Param, width: u32, height: u32, dpi: f32) -> Result<Self, Box<dyn Error>> {
+        // TODO modulate density by resolution
+        let life_lo = (101 - param.speed) as f32 / 3.0;
+        let life_hi = life_lo * 1.2;
+        let rate = (0.01 + param.density as f64 * 10.0) / life_lo as f64;
+
+        let mut monkey = match &param.font {
+            Some(path) => load_monkey(path, param.font_format, width, height)?,
+            None => load_monkey_static(width, height)?,
+        };
+        monkey.set_kerning(param.kerning);
+        monkey.set_field(param.field);
+        if let Some(text_size) = param.text_size {
+            monkey.set_pixel_size(text_size.pixels(dpi, height));
+        }
+        let mut phrases = load_phrases(param)?;
+        phrases.set_select(param.select);
+        let effects = param.effects.iter().map(|&u| Box::new(u) as Box<dyn Effect>).collect();
+        let mut words = DynWords::new(WORD_CAP, phrases, effects, life_hi, life_lo, rate);
+        words.set_easing(param.easing);
+        words.set_placement(param.placement);
+        let seed = param.seed.unwrap_or_else(|| thread_rng().gen());
+        println!("seed: {}", seed);
+        let rng = ChaCha8Rng::seed_from_u64(seed);
+        Ok(Self { monkey, words, rng })
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.words.update(dt, &self.monkey, &mut self.rng);
+    }
+
+    fn render<R: Renderer>(&mut self, renderer: &mut R) {
+        renderer.clear(Rgba::new(0.0, 0.0, 0.0, 1.0));
+        self.words.push(&mut self.monkey);
+        renderer.draw(&self.monkey);
+        self.monkey.clear();
+    }
+}
+
+fn load_monkey(
+    path: &Path,
+    format: Option<FontFormat>,
+    width: u32,
+    height: u32,
+) -> Result<FontMonkey, Box<dyn Error>> {
+    let folder = path.parent().unwrap_or_else(|| Path::new(""));
+    let font = path.file_name().ok_or("invalid font path")?;
+    FontMonkey::load(folder, font, format, width, height)
+}
+
+fn load_monkey_static(width: u32, height: u32) -> Result<FontMonkey, Box<dyn Error>> {
+    let font = bmfont_rs::text::from_str(include_str!("../assets/fonts/anton_latin.fnt"))?;
+    let image_data = include_bytes!("../assets/fonts/anton_latin_0.png");
+    FontMonkey::load_static(font, &[image_data], width, height)
+}
+
+fn load_phrases(param: &Param) -> Result<Phrases, Box<dyn Error>> {
+    if let Some(path) = &param.phrases {
+        return Phrases::load(path);
+    }
+    let (head, head_origin) = if param.head.is_empty() {
+        let words = words::parse(include_bytes!("../assets/words/moods.txt"), "moods.txt")?;
+        (words, "moods.txt".into())
+    } else {
+        (words::load(&param.head)?, origin(&param.head))
+    };
+    let (tail, tail_origin) = if param.tail.is_empty() {
+        let words = words::parse(include_bytes!("../assets/words/animals.txt"), "animals.txt")?;
+        (words, "animals.txt".into())
+    } else {
+        (words::load(&param.tail)?, origin(&param.tail))
+    };
+    Phrases::head_tail(head, &head_origin, tail, &tail_origin)
+}
+
+fn origin(paths: &[PathBuf]) -> String {
+    paths.iter().map(|u| u.display().to_string()).collect::<Vec<_>>().join(", ")
 }
 
 pub fn main() -> Result<(), Box<dyn Error>> {
     let param = Param::build(arg_matches());
+    let export = match (&param.headless, &param.export) {
+        (Some(dir), _) => Some(Export::png(dir)?),
+        (None, Some(path)) => Some(Export::create(path, param.width, param.height, param.fps)?),
+        (None, None) => None,
+    };
+    if let Some(export) = export {
+        if param.cpu {
+            return execute_cpu(&param, export);
+        }
+        let state = State::hidden(TITLE, param.width, param.height)?;
+        let mut demo = Demo::new(state)?;
+        demo.execute_offscreen(&param, export)?;
+        return Ok(());
+    }
     let state = if param.fullscreen {
         State::fullscreen(TITLE)?
     } else {
         State::windowed(TITLE, param.width, param.height)?
     };
-    // TODO modulate frames by frame rate, these assume 60Hz.
-    // TODO modulate density by resolution
-    let frame_lo = (101 - param.speed) * 20;
-    let frame_hi = frame_lo + (frame_lo / 5);
-    let p = 0.01 + param.density as f64 * 10.0;
-    let p = p / frame_lo as f64;
     let mut demo = Demo::new(state)?;
-    demo.execute(p, frame_hi, frame_lo)?;
+    demo.execute(&param)?;
     Ok(())
 }
 
@@ -177,6 +431,179 @@ fn arg_matches() -> ArgMatches<'static> {
                 .long("fullscreen")
                 .help("fullscreen, overrides width/ height"),
         )
+        .arg(
+            Arg::with_name("font")
+                .long("font")
+                .help("BMFont descriptor file, overrides the built-in font")
+                .takes_value(true)
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("font-format")
+                .long("font-format")
+                .help("BMFont descriptor format [default: auto detect]")
+                .takes_value(true)
+                .possible_values(&["text", "binary", "xml", "json"])
+                .requires("font")
+                .value_name("FORMAT"),
+        )
+        .arg(
+            Arg::with_name("field")
+                .long("field")
+                .help("font page texture content")
+                .takes_value(true)
+                .possible_values(&["bitmap", "sdf", "msdf"])
+                .requires("font")
+                .value_name("FIELD"),
+        )
+        .arg(
+            Arg::with_name("text-size")
+                .short("t")
+                .long("text-size")
+                .help("text size in points or percentage of screen height, e.g. 24pt or 5% [default: font size]")
+                .takes_value(true)
+                .validator(|u| TextSize::from_str(&u).map(|_| ()))
+                .value_name("SIZE"),
+        )
+        .arg(
+            Arg::with_name("head")
+                .long("head")
+                .help("head word list file or directory, overrides the built-in moods")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH"),
+        )
+        .arg(
+            Arg::with_name("tail")
+                .long("tail")
+                .help("tail word list file or directory, overrides the built-in animals")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH"),
+        )
+        .arg(
+            Arg::with_name("phrases")
+                .long("phrases")
+                .help("phrase template file, overrides head/ tail word lists")
+                .takes_value(true)
+                .conflicts_with_all(&["head", "tail"])
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("select")
+                .long("select")
+                .help("word selection, weighted random or shuffle bag [default: random]")
+                .takes_value(true)
+                .possible_values(&["random", "shuffle"])
+                .value_name("SELECT"),
+        )
+        .arg(
+            Arg::with_name("effect")
+                .long("effect")
+                .help("word animation effect, mixed at random if repeated or all [default: scatter]")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&Builtin::NAMES)
+                .possible_value("all")
+                .value_name("EFFECT"),
+        )
+        .arg(
+            Arg::with_name("ease-position")
+                .long("ease-position")
+                .help("word position/ rotation easing, entry[:exit] [default: linear]")
+                .takes_value(true)
+                .validator(|u| Transition::from_str(&u).map(|_| ()))
+                .value_name("EASE"),
+        )
+        .arg(
+            Arg::with_name("ease-alpha")
+                .long("ease-alpha")
+                .help("word color/ alpha easing, entry[:exit] [default: linear]")
+                .takes_value(true)
+                .validator(|u| Transition::from_str(&u).map(|_| ()))
+                .value_name("EASE"),
+        )
+        .arg(
+            Arg::with_name("ease-scale")
+                .long("ease-scale")
+                .help("word scale easing, entry[:exit] [default: linear]")
+                .takes_value(true)
+                .validator(|u| Transition::from_str(&u).map(|_| ()))
+                .value_name("EASE"),
+        )
+        .arg(
+            Arg::with_name("placement")
+                .long("placement")
+                .help(
+                    "word placement, random overlaps, avoid overlaps where possible or strictly \
+                     avoid overlaps, skipping words that don't fit [default: avoid]",
+                )
+                .takes_value(true)
+                .possible_values(&["random", "avoid", "strict"])
+                .value_name("PLACEMENT"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .help("random seed, replays a previous run [default: random]")
+                .takes_value(true)
+                .validator(|u| u.parse::<u64>().map(|_| ()).map_err(|e| e.to_string()))
+                .value_name("U64"),
+        )
+        .arg(
+            Arg::with_name("headless")
+                .long("headless")
+                .help("render offscreen to a PNG sequence in DIR, then exit")
+                .takes_value(true)
+                .conflicts_with("fullscreen")
+                .value_name("DIR"),
+        )
+        .arg(
+            Arg::with_name("frames")
+                .long("frames")
+                .help("headless/ export frame count")
+                .takes_value(true)
+                .default_value("600")
+                .validator(|u| is_u32_filter(&u, |_| true))
+                .value_name("COUNT"),
+        )
+        .arg(
+            Arg::with_name("export")
+                .long("export")
+                .help("render offscreen to an animated GIF or Y4M video FILE, then exit")
+                .takes_value(true)
+                .conflicts_with("fullscreen")
+                .value_name("FILE"),
+        )
+        .arg(
+            Arg::with_name("fps")
+                .long("fps")
+                .help("headless/ export frame rate")
+                .takes_value(true)
+                .default_value("60")
+                .validator(|u| is_u32_filter(&u, |u| u != 0))
+                .value_name("FPS"),
+        )
+        .arg(
+            Arg::with_name("cpu")
+                .long("cpu")
+                .help("headless/ export with the CPU rasteriser, no display or OpenGL required")
+                .requires("offscreen"),
+        )
+        // Also makes headless/ export mutually exclusive.
+        .group(ArgGroup::with_name("offscreen").args(&["headless", "export"]))
+        .arg(
+            Arg::with_name("screenshot-dir")
+                .long("screenshot-dir")
+                .help("F12 screenshot directory")
+                .takes_value(true)
+                .default_value(".")
+                .value_name("DIR"),
+        )
+        .arg(Arg::with_name("no-kerning").long("no-kerning").help("disable kerning pairs"))
         .arg(
             Arg::with_name("density")
                 .short("d")
@@ -211,6 +638,14 @@ where
     }
 }
 
+fn parse_size(v: &str) -> Result<f32, String> {
+    match v.parse::<f32>() {
+        Ok(u) if u > 0.0 && u.is_finite() => Ok(u),
+        Ok(u) => Err(format!("invalid value: {}", u)),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
 fn get_u32(args: &ArgMatches, name: &str) -> u32 {
     some_u32(args, name).unwrap_or_else(|| panic!("INTERNAL: default value error: {}", name))
 }
@@ -220,3 +655,15 @@ fn some_u32(args: &ArgMatches, name: &str) -> Option<u32> {
         u32::from_str(u).unwrap_or_else(|_| panic!("INTERNAL: parse value error: {}", name))
     })
 }
+
+fn get_transition(args: &ArgMatches, name: &str) -> Transition {
+    args.value_of(name).map_or(Transition::default(), |u| {
+        Transition::from_str(u).unwrap_or_else(|_| panic!("INTERNAL: parse value error: {}", name))
+    })
+}
+
+fn some_u64(args: &ArgMatches, name: &str) -> Option<u64> {
+    args.value_of(name).map(|u| {
+        u64::from_str(u).unwrap_or_else(|_| panic!("INTERNAL: parse value error: {}", name))
+    })
+}
@@ -0,0 +1,498 @@
+//! Template based phrase generation.
+//!
+//! A phrase file defines named word lists and weighted templates:
+//!
+//! ```text
+//! # Comment
+//! [mood]
+//! Happy
+//! Sad
+//!
+//! [animal]
+//! # Weighted entry, as per word list files.
+//! Cat|3
+//! # Include word list files/ directories, relative to the phrase file.
+//! @animals.txt
+//!
+//! [templates]
+//! # Optional weight prefix, defaults to 1. Any other text before a ':' belongs to the
+//! # template, e.g. 'Note: {mood}' is the literal 'Note: ' followed by a mood, weight 1.
+//! 3: {mood} {animal}
+//! {^a} {animal} that is {mood}
+//! ```
+//!
+//! Template placeholders:
+//! - `{name}`: a random entry from the named list.
+//! - `{^name}`: as above, with the first letter capitalized.
+//! - `{a}`/ `{^a}`: the article 'a' or 'an', in agreement with the following word.
+
+use crate::words::{self, Entry};
+
+use rand::prelude::*;
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::result::Result;
+use std::str::FromStr;
+
+const TEMPLATES: &str = "templates";
+const ARTICLE: &str = "a";
+
+/// Word list entry selection strategy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Select {
+    /// Weighted random selection, with replacement.
+    Random,
+    /// Draw every entry, weight times, in random order before repeating.
+    Shuffle,
+}
+
+impl FromStr for Select {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "shuffle" => Ok(Self::Shuffle),
+            u => Err(format!("invalid select: {}", u)),
+        }
+    }
+}
+
+pub struct Phrases {
+    lists: Vec<WordList>,
+    templates: Vec<Template>,
+    weights: Vec<u32>,
+    total: u64,
+    select: Select,
+    /// (list, entry) picks of the last shuffled phrase.
+    last: Vec<(usize, usize)>,
+}
+
+impl Phrases {
+    /// The classic "head tail" phrase. `head_origin`/ `tail_origin` identify the list sources in
+    /// error messages.
+    pub fn head_tail(
+        head: Vec<Entry>,
+        head_origin: &str,
+        tail: Vec<Entry>,
+        tail_origin: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        let lists = vec![
+            ("head".to_owned(), head_origin.to_owned(), head),
+            ("tail".to_owned(), tail_origin.to_owned(), tail),
+        ];
+        Self::build(lists, vec![(1, "{head} {tail}".to_owned())], "head tail")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let src = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let folder = path.parent().unwrap_or_else(|| Path::new(""));
+        Self::parse(&src, &path.display().to_string(), folder)
+    }
+
+    /// `origin` identifies the source in error messages, `folder` resolves '@' includes.
+    pub fn parse(src: &str, origin: &str, folder: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut lists: Vec<(String, String, Vec<Entry>)> = Vec::default();
+        let mut templates = Vec::default();
+        let mut section = None;
+        for (i, line) in src.lines().enumerate() {
+            let err = |e: &str| format!("{}:{}: {}", origin, i + 1, e);
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|u| u.strip_suffix(']')) {
+                let name = name.trim();
+                if name == TEMPLATES {
+                    section = Some(None);
+                } else if name.is_empty() || name == ARTICLE {
+                    return Err(err(&format!("invalid list name: '{}'", name)).into());
+                } else {
+                    let index = match lists.iter().position(|(u, _, _)| u == name) {
+                        Some(index) => index,
+                        None => {
+                            lists.push((name.to_owned(), origin.to_owned(), Vec::default()));
+                            lists.len() - 1
+                        }
+                    };
+                    section = Some(Some(index));
+                }
+                continue;
+            }
+            match section {
+                None => return Err(err("expected section").into()),
+                Some(None) => templates.push(parse_weight(line)),
+                Some(Some(index)) => match line.strip_prefix('@') {
+                    Some(path) => lists[index].2.extend(words::load(&[folder.join(path.trim())])?),
+                    None => lists[index].2.push(Entry::parse(line).map_err(|e| err(&e))?),
+                },
+            }
+        }
+        Self::build(lists, templates, origin)
+    }
+
+    /// `lists` are (name, origin, entries).
+    fn build(
+        lists: Vec<(String, String, Vec<Entry>)>,
+        templates: Vec<(u32, String)>,
+        origin: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        if templates.is_empty() {
+            return Err(format!("{}: no templates", origin).into());
+        }
+        let names: HashMap<&str, usize> =
+            lists.iter().enumerate().map(|(i, (name, _, _))| (name.as_str(), i)).collect();
+        let weights: Vec<u32> = templates.iter().map(|(weight, _)| *weight).collect();
+        let templates = templates
+            .iter()
+            .map(|(_, template)| Template::parse(template, &names))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{}: {}", origin, e))?;
+        for template in &templates {
+            for token in &template.0 {
+                if let Token::Word { list, .. } = token {
+                    let (name, origin, entries) = &lists[*list];
+                    if entries.iter().all(|u| u.weight == 0) {
+                        return Err(format!("{}: empty word list: {}", origin, name).into());
+                    }
+                }
+            }
+        }
+        let total = weights.iter().map(|&u| u as u64).sum();
+        if total == 0 {
+            return Err(format!("{}: templates: all weights zero", origin).into());
+        }
+        let lists = lists.into_iter().map(|(_, _, list)| WordList::new(list)).collect();
+        Ok(Self { lists, templates, weights, total, select: Select::Random, last: Vec::default() })
+    }
+
+    /// Defaults to `Select::Random`.
+    pub fn set_select(&mut self, select: Select) {
+        self.select = select;
+    }
+
+    pub fn gen<R: Rng>(&mut self, rng: &mut R) -> String {
+        let template = &self.templates[weighted(&self.weights, self.total, rng)];
+        let select = self.select;
+        let lists = &mut self.lists;
+        let picks: Vec<usize> = template
+            .0
+            .iter()
+            .map(|token| match token {
+                Token::Word { list, .. } => lists[*list].pick(select, rng),
+                _ => 0,
+            })
+            .collect();
+        self.last.clear();
+        if select == Select::Shuffle {
+            self.last.extend(template.0.iter().zip(&picks).filter_map(
+                |(token, &pick)| match token {
+                    Token::Word { list, .. } => Some((*list, pick)),
+                    _ => None,
+                },
+            ));
+        }
+        let parts: Vec<Part> = template
+            .0
+            .iter()
+            .zip(picks)
+            .map(|(token, pick)| match token {
+                Token::Text(text) => Part::Text(text),
+                Token::Word { list, cap } => Part::Word(&self.lists[*list].words[pick], *cap),
+                Token::Article { cap } => Part::Article(*cap),
+            })
+            .collect();
+        let mut phrase = String::default();
+        for (i, part) in parts.iter().enumerate() {
+            match *part {
+                Part::Text(text) => phrase.push_str(text),
+                Part::Word(word, cap) => push_word(&mut phrase, word, cap),
+                Part::Article(cap) => {
+                    let article = if next_is_vowel(&parts[i + 1..]) { "an" } else { "a" };
+                    push_word(&mut phrase, article, cap);
+                }
+            }
+        }
+        phrase
+    }
+
+    /// Return the last generated phrase's entries to their shuffle bags, e.g. if the phrase was
+    /// not used.
+    pub fn reject(&mut self) {
+        for (list, pick) in self.last.drain(..) {
+            self.lists[list].unpick(pick);
+        }
+    }
+}
+
+struct WordList {
+    words: Vec<String>,
+    weights: Vec<u32>,
+    total: u64,
+    /// Remaining draws per entry, so heavy weights cost nothing extra.
+    bag: Vec<u32>,
+    left: u64,
+}
+
+impl WordList {
+    /// Lists may be empty, as long as no template draws from them.
+    fn new(entries: Vec<Entry>) -> Self {
+        let weights: Vec<u32> = entries.iter().map(|u| u.weight).collect();
+        let total = weights.iter().map(|&u| u as u64).sum();
+        let words = entries.into_iter().map(|u| u.word).collect();
+        Self { words, weights, total, bag: Vec::default(), left: 0 }
+    }
+
+    fn pick<R: Rng>(&mut self, select: Select, rng: &mut R) -> usize {
+        match select {
+            Select::Random => weighted(&self.weights, self.total, rng),
+            Select::Shuffle => {
+                if self.left == 0 {
+                    self.bag.clone_from(&self.weights);
+                    self.left = self.total;
+                }
+                let index = weighted(&self.bag, self.left, rng);
+                self.bag[index] -= 1;
+                self.left -= 1;
+                index
+            }
+        }
+    }
+
+    fn unpick(&mut self, index: usize) {
+        self.bag[index] += 1;
+        self.left += 1;
+    }
+}
+
+enum Token {
+    Text(String),
+    Word { list: usize, cap: bool },
+    Article { cap: bool },
+}
+
+struct Template(Vec<Token>);
+
+impl Template {
+    fn parse(src: &str, names: &HashMap<&str, usize>) -> Result<Self, String> {
+        let mut tokens = Vec::default();
+        let mut rest = src;
+        while let Some(open) = rest.find('{') {
+            if open != 0 {
+                tokens.push(Token::Text(rest[..open].to_owned()));
+            }
+            let close =
+                rest[open..].find('}').ok_or_else(|| format!("unclosed placeholder: '{}'", src))?;
+            let name = rest[open + 1..open + close].trim();
+            let (name, cap) = match name.strip_prefix('^') {
+                Some(u) => (u, true),
+                None => (name, false),
+            };
+            if name == ARTICLE {
+                tokens.push(Token::Article { cap });
+            } else {
+                let list = *names.get(name).ok_or_else(|| format!("unknown list: '{}'", name))?;
+                tokens.push(Token::Word { list, cap });
+            }
+            rest = &rest[open + close + 1..];
+        }
+        if !rest.is_empty() {
+            tokens.push(Token::Text(rest.to_owned()));
+        }
+        Ok(Self(tokens))
+    }
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Word(&'a str, bool),
+    Article(bool),
+}
+
+fn parse_weight(line: &str) -> (u32, String) {
+    if let Some((weight, template)) = line.split_once(':') {
+        if let Ok(weight) = weight.trim().parse::<u32>() {
+            return (weight, template.trim().to_owned());
+        }
+    }
+    (1, line.to_owned())
+}
+
+/// Weighted random index, `total` being the non-zero sum of `weights`.
+fn weighted<R: Rng>(weights: &[u32], total: u64, rng: &mut R) -> usize {
+    let mut k = rng.gen_range(0..total);
+    weights
+        .iter()
+        .position(|&u| match k.checked_sub(u as u64) {
+            Some(v) => {
+                k = v;
+                false
+            }
+            None => true,
+        })
+        .expect("INTERNAL: weight total")
+}
+
+fn push_word(phrase: &mut String, word: &str, cap: bool) {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(c) if cap => {
+            phrase.extend(c.to_uppercase());
+            phrase.push_str(chars.as_str());
+        }
+        _ => phrase.push_str(word),
+    }
+}
+
+fn next_is_vowel(parts: &[Part]) -> bool {
+    for part in parts {
+        let text = match *part {
+            Part::Text(text) => text,
+            Part::Word(word, _) => word,
+            Part::Article(_) => "a",
+        };
+        if let Some(c) = text.chars().find(|u| !u.is_whitespace()) {
+            return matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u');
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+    use std::env;
+
+    fn parse(src: &str) -> Result<Phrases, Box<dyn Error>> {
+        Phrases::parse(src, "test", Path::new(""))
+    }
+
+    fn gen_set(phrases: &mut Phrases, n: usize) -> HashSet<String> {
+        let mut rng = StdRng::seed_from_u64(7);
+        (0..n).map(|_| phrases.gen(&mut rng)).collect()
+    }
+
+    fn set(phrases: &[&str]) -> HashSet<String> {
+        phrases.iter().map(|&u| u.to_owned()).collect()
+    }
+
+    #[test]
+    fn sections() {
+        let src =
+            "# Mood\n[mood]\nHappy\n[animal]\nCat\n[mood]\nSad\n[templates]\n{mood} {animal}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 64), set(&["Happy Cat", "Sad Cat"]));
+    }
+
+    #[test]
+    fn includes() {
+        let folder = env::temp_dir().join(format!("moody_animals_phrases_{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("animals.txt"), "Dog\n# Comment\nEel|2\n").unwrap();
+        let src = "[animal]\nCat\n@ animals.txt\n[templates]\n{animal}\n";
+        let phrases = Phrases::parse(src, "test", &folder);
+        fs::remove_dir_all(&folder).unwrap();
+        assert_eq!(gen_set(&mut phrases.unwrap(), 64), set(&["Cat", "Dog", "Eel"]));
+    }
+
+    #[test]
+    fn missing_include() {
+        assert!(parse("[animal]\n@missing.txt\n[templates]\n{animal}\n").is_err());
+    }
+
+    #[test]
+    fn weights() {
+        let src = "[mood]\nHappy\nSad|0\n[templates]\n0: never\n2: {mood}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 64), set(&["Happy"]));
+    }
+
+    #[test]
+    fn weight_prefix_text() {
+        let src = "[mood]\nHappy\n[templates]\nNote: {mood}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 1), set(&["Note: Happy"]));
+    }
+
+    #[test]
+    fn capitalize() {
+        let src = "[animal]\ncat\n[templates]\n{^animal} {animal}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 1), set(&["Cat cat"]));
+    }
+
+    #[test]
+    fn article() {
+        let src = "[animal]\nowl\n[fruit]\npear\n[templates]\n{^a} {animal} and {a} {^fruit}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 1), set(&["An owl and a Pear"]));
+    }
+
+    #[test]
+    fn shuffle() {
+        let mut phrases = parse("[mood]\nHappy|2\nSad\n[templates]\n{mood}\n").unwrap();
+        phrases.set_select(Select::Shuffle);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..4 {
+            let mut draw: Vec<String> = (0..3).map(|_| phrases.gen(&mut rng)).collect();
+            draw.sort();
+            assert_eq!(draw, ["Happy", "Happy", "Sad"]);
+        }
+    }
+
+    #[test]
+    fn shuffle_reject() {
+        let mut phrases = parse("[mood]\nHappy\nSad\n[templates]\n{mood}\n").unwrap();
+        phrases.set_select(Select::Shuffle);
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..4 {
+            phrases.gen(&mut rng);
+            phrases.reject();
+            let mut draw: Vec<String> = (0..2).map(|_| phrases.gen(&mut rng)).collect();
+            draw.sort();
+            assert_eq!(draw, ["Happy", "Sad"]);
+        }
+    }
+
+    #[test]
+    fn unused_empty_list() {
+        let src = "[mood]\nHappy\n\n[colour]\n\n[templates]\n{mood}\n";
+        assert_eq!(gen_set(&mut parse(src).unwrap(), 1), set(&["Happy"]));
+    }
+
+    fn err(src: &str) -> String {
+        match parse(src) {
+            Ok(_) => panic!("expected error: {}", src),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn head_tail() {
+        let entries = |words: &[&str]| words.iter().map(|&u| Entry::parse(u).unwrap()).collect();
+        let mut phrases =
+            Phrases::head_tail(entries(&["Happy"]), "moods", entries(&["Cat"]), "animals").unwrap();
+        assert_eq!(gen_set(&mut phrases, 1), set(&["Happy Cat"]));
+        let err = Phrases::head_tail(entries(&["Happy"]), "moods", entries(&["Cat|0"]), "animals")
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "animals: empty word list: tail");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(err("Happy\n"), "test:1: expected section");
+        assert_eq!(err("[a]\n"), "test:1: invalid list name: 'a'");
+        assert_eq!(
+            err("[mood]\nHappy|x\n"),
+            "test:2: invalid weight: 'x': invalid digit found in string"
+        );
+        assert_eq!(err("[mood]\nHappy\n"), "test: no templates");
+        assert_eq!(err("[templates]\n{mood}\n"), "test: unknown list: 'mood'");
+        assert_eq!(
+            err("[mood]\nHappy\n[templates]\n{mood\n"),
+            "test: unclosed placeholder: '{mood'"
+        );
+        assert_eq!(err("[mood]\nHappy|0\n[templates]\n{mood}\n"), "test: empty word list: mood");
+    }
+}
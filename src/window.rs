@@ -0,0 +1,55 @@
+//! SDL2 window and OpenGL context creation.
+
+use sdl2::video::{GLContext, GLProfile, SwapInterval, Window, WindowBuildError};
+use sdl2::{Sdl, VideoSubsystem};
+
+use std::error::Error;
+use std::result::Result;
+
+/// SDL window with a current OpenGL 3.3 core context, loaded and ready for `glx`.
+pub struct State {
+    pub sdl: Sdl,
+    pub video_subsystem: VideoSubsystem,
+    pub window: Window,
+    _context: GLContext,
+}
+
+impl State {
+    pub fn windowed(title: &str, width: u32, height: u32) -> Result<Self, Box<dyn Error>> {
+        Self::build(|vs| vs.window(title, width, height).resizable().opengl().build())
+    }
+
+    /// Hidden window, for offscreen rendering. Without a display, SDL may need
+    /// `SDL_VIDEODRIVER=offscreen`.
+    pub fn hidden(title: &str, width: u32, height: u32) -> Result<Self, Box<dyn Error>> {
+        Self::build(|vs| vs.window(title, width, height).hidden().opengl().build())
+    }
+
+    pub fn fullscreen(title: &str) -> Result<Self, Box<dyn Error>> {
+        Self::build(|vs| vs.window(title, 0, 0).fullscreen_desktop().opengl().build())
+    }
+
+    fn build<W>(window_builder: W) -> Result<Self, Box<dyn Error>>
+    where
+        W: FnOnce(&VideoSubsystem) -> Result<Window, WindowBuildError>,
+    {
+        let sdl = sdl2::init().map_err(|e| format!("SDL init: {}", e))?;
+        let video_subsystem = sdl.video().map_err(|e| format!("SDL video: {}", e))?;
+
+        let gl_attr = video_subsystem.gl_attr();
+        gl_attr.set_context_profile(GLProfile::Core);
+        gl_attr.set_context_version(3, 3);
+
+        let window = window_builder(&video_subsystem).map_err(|e| format!("SDL window: {}", e))?;
+
+        let context =
+            window.gl_create_context().map_err(|e| format!("OpenGL 3.3 context: {}", e))?;
+        gl::load_with(|name| video_subsystem.gl_get_proc_address(name) as *const _);
+
+        debug_assert_eq!(gl_attr.context_profile(), GLProfile::Core);
+        debug_assert_eq!(gl_attr.context_version(), (3, 3));
+
+        video_subsystem.gl_set_swap_interval(SwapInterval::VSync)?;
+        Ok(Self { sdl, video_subsystem, window, _context: context })
+    }
+}
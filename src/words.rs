@@ -0,0 +1,109 @@
+//! Word list loading.
+//!
+//! Word lists are UTF-8 text, one entry per line. Leading/ trailing whitespace is trimmed, blank
+//! lines and lines starting with '#' are ignored. Entries may carry a selection weight suffix,
+//! e.g. `Happy|3`, defaulting to 1.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::result::Result;
+use std::str;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    pub word: String,
+    pub weight: u32,
+}
+
+impl Entry {
+    /// Parse a single trimmed, non-comment line.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        match line.rsplit_once('|') {
+            Some((word, weight)) => match weight.trim().parse() {
+                Ok(weight) => Ok(Self { word: word.trim().to_owned(), weight }),
+                Err(err) => Err(format!("invalid weight: '{}': {}", weight, err)),
+            },
+            None => Ok(Self { word: line.to_owned(), weight: 1 }),
+        }
+    }
+}
+
+/// Load and concatenate word lists. Directories are expanded to the files they contain, in
+/// file name order.
+pub fn load(paths: &[PathBuf]) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let mut words = Vec::default();
+    for path in paths {
+        if path.is_dir() {
+            let mut files =
+                fs::read_dir(path)?.map(|u| u.map(|u| u.path())).collect::<Result<Vec<_>, _>>()?;
+            files.retain(|u| u.is_file());
+            files.sort();
+            for file in &files {
+                words.extend(load_file(file)?);
+            }
+        } else {
+            words.extend(load_file(path)?);
+        }
+    }
+    Ok(words)
+}
+
+fn load_file(path: &Path) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse(&bytes, &path.display().to_string())
+}
+
+/// `origin` identifies the source in error messages.
+pub fn parse(bytes: &[u8], origin: &str) -> Result<Vec<Entry>, Box<dyn Error>> {
+    let mut words = Vec::default();
+    for (i, line) in bytes.split(|&u| u == b'\n').enumerate() {
+        let line = str::from_utf8(line)
+            .map_err(|e| format!("{}:{}: invalid UTF-8: {}", origin, i + 1, e))?
+            .trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            words.push(Entry::parse(line).map_err(|e| format!("{}:{}: {}", origin, i + 1, e))?);
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(word: &str, weight: u32) -> Entry {
+        Entry { word: word.to_owned(), weight }
+    }
+
+    #[test]
+    fn comments_blank_lines() {
+        let words =
+            parse(b"# Moods\n\nHappy\n  \n  # Indented comment\n  Sad  \n", "test").unwrap();
+        assert_eq!(words, [entry("Happy", 1), entry("Sad", 1)]);
+    }
+
+    #[test]
+    fn crlf() {
+        let words = parse(b"Happy\r\n\r\nSad|2\r\n", "test").unwrap();
+        assert_eq!(words, [entry("Happy", 1), entry("Sad", 2)]);
+    }
+
+    #[test]
+    fn weights() {
+        let words = parse(b"Happy|3\nSad | 0\nSo|so|2\n", "test").unwrap();
+        assert_eq!(words, [entry("Happy", 3), entry("Sad", 0), entry("So|so", 2)]);
+    }
+
+    #[test]
+    fn invalid_weight() {
+        let err = parse(b"Happy\nSad|-1\n", "test").unwrap_err().to_string();
+        assert!(err.starts_with("test:2: invalid weight: '-1'"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let err = parse(b"Happy\n\nS\xffd\n", "test").unwrap_err().to_string();
+        assert!(err.starts_with("test:3: invalid UTF-8"), "{}", err);
+    }
+}
@@ -0,0 +1,191 @@
+//! Golden image regression tests, rendered with the CPU rasteriser.
+//!
+//! Reference images live in `tests/golden`. Following an intentional rendering change, regenerate
+//! them with:
+//!
+//! `GOLDEN_REGENERATE=1 cargo test golden`
+//!
+//! On mismatch the rendered image is written to `target/golden` for inspection.
+
+use moody_animals::glx::types::*;
+use moody_animals::glx::{Align, CpuRenderer, Field, FontFormat, FontMonkey, Layout, Renderer};
+
+use bmfont_rs::{Font, Packing};
+use image::RgbaImage;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Maximum per channel difference.
+const TOLERANCE: u8 = 2;
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 80;
+
+const WHITE: Rgba = Rgb::WHITE.into_rgba(1.0);
+
+fn font() -> Font {
+    bmfont_rs::text::from_str(include_str!("../assets/fonts/anton_latin.fnt")).unwrap()
+}
+
+fn monkey_with(font: Font, width: u32, height: u32) -> FontMonkey {
+    let image_data = include_bytes!("../assets/fonts/anton_latin_0.png");
+    FontMonkey::load_static(font, &[image_data], width, height).unwrap()
+}
+
+fn monkey(width: u32, height: u32) -> FontMonkey {
+    monkey_with(font(), width, height)
+}
+
+fn render<F: FnOnce(&mut FontMonkey)>(
+    monkey: &mut FontMonkey,
+    width: u32,
+    height: u32,
+    push: F,
+) -> RgbaImage {
+    let mut renderer = CpuRenderer::new(width, height);
+    renderer.clear(Rgb::BLACK.into_rgba(1.0));
+    push(monkey);
+    renderer.draw(monkey);
+    monkey.clear();
+    renderer.into_image()
+}
+
+fn render_str(monkey: &mut FontMonkey, string: &str) -> RgbaImage {
+    render(monkey, WIDTH, HEIGHT, |u| {
+        u.push_str(string, P2::new(-0.95, 0.9), WHITE);
+    })
+}
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn check(name: &str, image: &RgbaImage) {
+    // Every test clears opaque: drawing must not punch translucent holes into the target.
+    let translucent = image.pixels().filter(|u| u.0[3] != 255).count();
+    assert_eq!(translucent, 0, "{}: translucent pixels over an opaque clear", name);
+    let path = golden_dir().join(format!("{}.png", name));
+    if env::var_os("GOLDEN_REGENERATE").is_some() {
+        fs::create_dir_all(golden_dir()).unwrap();
+        image.save(&path).unwrap();
+        return;
+    }
+    let golden =
+        image::open(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err)).into_rgba8();
+    assert_eq!(golden.dimensions(), image.dimensions(), "{}: dimensions", name);
+    let mismatches = golden
+        .pixels()
+        .zip(image.pixels())
+        .filter(|(u, v)| u.0.iter().zip(v.0.iter()).any(|(&u, &v)| u.max(v) - u.min(v) > TOLERANCE))
+        .count();
+    if mismatches != 0 {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden");
+        fs::create_dir_all(&dir).unwrap();
+        let actual = dir.join(format!("{}.png", name));
+        image.save(&actual).unwrap();
+        panic!(
+            "{}: {} pixels differ by more than {}, rendered: {}",
+            name,
+            mismatches,
+            TOLERANCE,
+            actual.display()
+        );
+    }
+}
+
+#[test]
+fn golden_text() {
+    check("text", &render_str(&mut monkey(WIDTH, HEIGHT), "Moody Animals"));
+}
+
+#[test]
+fn golden_formats() {
+    let font = font();
+    let descriptors = [
+        bmfont_rs::binary::to_vec(&font).unwrap(),
+        bmfont_rs::xml::to_vec(&font).unwrap(),
+        serde_json::to_vec(&font).unwrap(),
+    ];
+    for bytes in descriptors.iter() {
+        let font = FontFormat::detect(bytes).parse(bytes).unwrap();
+        check("text", &render_str(&mut monkey_with(font, WIDTH, HEIGHT), "Moody Animals"));
+    }
+}
+
+#[test]
+fn golden_kerning() {
+    let mut monkey = monkey(WIDTH, HEIGHT);
+    check("kerning_on", &render_str(&mut monkey, "LTLYYJ"));
+    monkey.set_kerning(false);
+    check("kerning_off", &render_str(&mut monkey, "LTLYYJ"));
+}
+
+#[test]
+fn golden_fallback() {
+    let mut monkey = monkey(WIDTH, HEIGHT);
+    check("fallback", &render_str(&mut monkey, "Ωmega"));
+    monkey.set_fallback(None);
+    check("fallback_none", &render_str(&mut monkey, "Ωmega"));
+}
+
+#[test]
+fn golden_scale() {
+    let mut monkey = monkey(WIDTH, HEIGHT);
+    monkey.set_scale(0.5);
+    check("scale_half", &render_str(&mut monkey, "Moody Animals"));
+    monkey.set_scale(1.5);
+    check("scale_large", &render_str(&mut monkey, "Moody"));
+}
+
+#[test]
+fn golden_affine() {
+    let mut monkey = monkey(WIDTH, HEIGHT * 2);
+    let transform = Affine2::rotate(0.4).then(Affine2::skew(0.2, 0.0));
+    let image = render(&mut monkey, WIDTH, HEIGHT * 2, |u| {
+        u.push_str_affine("Spin", P2::new(-0.5, -0.2), &transform, WHITE);
+    });
+    check("affine", &image);
+}
+
+#[test]
+fn golden_layout() {
+    let mut monkey = monkey(WIDTH, HEIGHT * 3);
+    monkey.set_scale(0.5);
+    let layout =
+        Layout::build(&monkey, "Moody Animals\nThe quick brown fox", Some(0.8), Align::Center);
+    let image = render(&mut monkey, WIDTH, HEIGHT * 3, |u| {
+        layout.push(u, P2::new(-0.5, 0.9), WHITE);
+    });
+    check("layout", &image);
+}
+
+#[test]
+fn golden_blend() {
+    let mut monkey = monkey(WIDTH, HEIGHT);
+    let image = render(&mut monkey, WIDTH, HEIGHT, |u| {
+        u.push_str("Moody", P2::new(-0.95, 0.9), Rgb::RED.into_rgba(0.5));
+        u.push_str("Moody", P2::new(-0.85, 0.8), Rgb::CYAN.into_rgba(0.5));
+    });
+    check("blend", &image);
+}
+
+#[test]
+fn golden_field() {
+    // Bitmap coverage thresholded as a distance field: exercises the Sdf path.
+    let mut monkey = monkey(WIDTH, HEIGHT);
+    monkey.set_field(Field::Sdf);
+    check("field_sdf", &render_str(&mut monkey, "Moody Animals"));
+}
+
+#[test]
+fn golden_luma_alpha() {
+    // The Anton page is single channel: declare the glyph in alpha, with RGB set to one.
+    let mut font = font();
+    font.common.alpha_chnl = Packing::Glyph;
+    font.common.red_chnl = Packing::One;
+    font.common.green_chnl = Packing::One;
+    font.common.blue_chnl = Packing::One;
+    check("text", &render_str(&mut monkey_with(font, WIDTH, HEIGHT), "Moody Animals"));
+}